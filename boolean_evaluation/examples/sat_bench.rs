@@ -0,0 +1,72 @@
+//! Times brute force enumeration, DPLL and CDCL on the same random 3-SAT formulas.
+//!
+//! `cargo run --release --example sat_bench`
+
+use boolean_evaluation::assignment::Assignment;
+use boolean_evaluation::cnf::{cnf_clauses, node_to_cnf};
+use boolean_evaluation::tree::Tree;
+use boolean_evaluation::{cdcl, dpll};
+use std::str::FromStr;
+use std::time::Instant;
+
+// Random 3-SAT at the hard clause/variable ratio of 4.26, written as an RPN formula.
+fn random_3sat(seed: u64, variables: usize) -> String {
+    let mut state = seed;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    let clauses = (variables as f64 * 4.26) as usize;
+    let mut formula = String::new();
+    for i in 0..clauses {
+        for j in 0..3 {
+            formula.push_str(&format!("[x{}]", next() % variables));
+            if next() % 2 == 1 {
+                formula.push('!');
+            }
+            if j > 0 {
+                formula.push('|');
+            }
+        }
+        if i > 0 {
+            formula.push('&');
+        }
+    }
+    formula
+}
+
+fn main() {
+    println!("| vars | brute force | dpll | cdcl | sat |");
+    println!("|------|-------------|------|------|-----|");
+    for variables in [10, 14, 18, 22, 50, 100, 150] {
+        let tree = Tree::from_str(&random_3sat(variables as u64, variables)).unwrap();
+        let count = tree.variable_count();
+        let clauses = cnf_clauses(&node_to_cnf(tree.root.clone()));
+
+        let brute_force = if count <= 18 {
+            let start = Instant::now();
+            (0..1u64 << count)
+                .any(|bitfield| tree.root.eval_with(&Assignment::from_bits(bitfield, count)));
+            format!("{:?}", start.elapsed())
+        } else {
+            String::from("-")
+        };
+        let dpll = if count <= 100 {
+            let start = Instant::now();
+            dpll::solve(&clauses, count);
+            format!("{:?}", start.elapsed())
+        } else {
+            String::from("-")
+        };
+        let start = Instant::now();
+        let sat = cdcl::solve(&clauses, count).is_some();
+        let cdcl = start.elapsed();
+
+        println!(
+            "| {} | {} | {} | {:?} | {} |",
+            count, brute_force, dpll, cdcl, sat
+        );
+    }
+}
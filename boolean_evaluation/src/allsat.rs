@@ -0,0 +1,111 @@
+use crate::assignment::Assignment;
+use crate::cdcl::Cdcl;
+use crate::literal::{Clause, Literal};
+use crate::model::Model;
+
+/// Iterator over the satisfying assignments of a formula, built by
+/// [`Tree::models`](crate::tree::Tree::models) or
+/// [`Tree::projected_models`](crate::tree::Tree::projected_models).
+///
+/// Each model is found by the SAT solver, then excluded from the following searches with a
+/// blocking clause over the projected variables added to the same solver, so nothing is computed
+/// before it is asked for and what was learned carries over from one model to the next.
+pub struct Models {
+    names: Vec<String>,
+    projection: Vec<usize>,
+    solver: Cdcl,
+    done: bool,
+}
+
+impl Models {
+    pub(crate) fn new(
+        names: Vec<String>,
+        projection: Vec<usize>,
+        clauses: Vec<Clause>,
+        variable_count: usize,
+    ) -> Self {
+        Self {
+            names,
+            projection,
+            solver: Cdcl::with_clauses(&clauses, variable_count),
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Models {
+    type Item = Model;
+
+    fn next(&mut self) -> Option<Model> {
+        if self.done {
+            return None;
+        }
+        let Some(assignment) = self.solver.solve() else {
+            self.done = true;
+            return None;
+        };
+        let blocking: Clause = self
+            .projection
+            .iter()
+            .map(|&v| Literal {
+                variable: v,
+                negated: assignment.get(v),
+            })
+            .collect();
+        // with nothing to block on, the single empty model is the only one
+        self.done = blocking.is_empty() || !self.solver.add_clause(&blocking);
+
+        let values: Assignment = self.projection.iter().map(|&v| assignment.get(v)).collect();
+        Some(Model::new(self.names.clone(), values))
+    }
+}
+
+#[cfg(test)]
+mod allsat_test {
+    use crate::tree::Tree;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_all_models() {
+        let tree = Tree::from_str("AB|").unwrap();
+        let models: HashSet<Vec<bool>> = tree
+            .models()
+            .inspect(|m| assert!(tree.root.eval_with(m.assignment())))
+            .map(|m| m.assignment().values().to_vec())
+            .collect();
+        assert_eq!(
+            models,
+            HashSet::from([vec![true, false], vec![false, true], vec![true, true]])
+        );
+    }
+
+    #[test]
+    fn test_no_models() {
+        assert_eq!(Tree::from_str("AA!&").unwrap().models().count(), 0);
+        assert_eq!(Tree::from_str("0").unwrap().models().count(), 0);
+        assert_eq!(Tree::from_str("1").unwrap().models().count(), 1);
+    }
+
+    #[test]
+    fn test_projected_models() {
+        let tree = Tree::from_str("AB|C&D|").unwrap();
+        assert_eq!(tree.models().count(), 11);
+        let models: Vec<_> = tree.projected_models(&["D", "A"]).collect();
+        assert_eq!(models.len(), 4);
+        assert_eq!(models[0].variables(), &["D", "A"]);
+        let tree = Tree::from_str("AB|C&").unwrap();
+        assert_eq!(tree.projected_models(&["C"]).count(), 1);
+        assert_eq!(tree.projected_models(&[]).count(), 1);
+    }
+
+    #[test]
+    fn test_models_lazy() {
+        // 2^60 models, only the first few are ever computed
+        let names: Vec<String> = (0..60).map(|i| format!("[x{}]", i)).collect();
+        let mut formula = names.concat();
+        formula.push_str(&"|".repeat(59));
+        let tree = Tree::from_str(&formula).unwrap();
+        assert_eq!(tree.models().take(5).count(), 5);
+    }
+}
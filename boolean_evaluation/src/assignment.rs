@@ -0,0 +1,94 @@
+/// Values given to the variables of a formula, indexed like [`Tree::variable_list`].
+///
+/// An assignment lives apart from the formula it is used with, so the same [`Node`] can be
+/// evaluated under any number of them with [`Node::eval_with`].
+/// Variables past the end of the assignment read as `false`.
+///
+/// [`Tree::variable_list`]: crate::tree::Tree::variable_list
+/// [`Node`]: crate::node::Node
+/// [`Node::eval_with`]: crate::node::Node::eval_with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Assignment {
+    values: Vec<bool>,
+}
+
+impl Assignment {
+    /// All `len` variables set to `false`.
+    pub fn new(len: usize) -> Self {
+        Self {
+            values: vec![false; len],
+        }
+    }
+
+    /// Variable `i` takes the value of bit `i` of `bitfield`, as in the rows of a truth table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is over 64, the bits of `bitfield`.
+    pub fn from_bits(bitfield: u64, len: usize) -> Self {
+        assert!(len <= 64, "At most 64 variables fit in a bitfield");
+        (0..len).map(|i| (bitfield >> i) & 1 == 1).collect()
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.values.get(index).copied().unwrap_or(false)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        if index >= self.values.len() {
+            self.values.resize(index + 1, false);
+        }
+        self.values[index] = value;
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &[bool] {
+        &self.values
+    }
+}
+
+impl From<Vec<bool>> for Assignment {
+    fn from(values: Vec<bool>) -> Self {
+        Self { values }
+    }
+}
+
+impl FromIterator<bool> for Assignment {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod assignment_test {
+    use crate::assignment::Assignment;
+
+    #[test]
+    fn test_from_bits() {
+        let assignment = Assignment::from_bits(0b101, 3);
+        assert_eq!(assignment.values(), &[true, false, true]);
+        assert!(!assignment.get(7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_bits_length() {
+        Assignment::from_bits(0, 65);
+    }
+
+    #[test]
+    fn test_set_grows() {
+        let mut assignment = Assignment::new(1);
+        assignment.set(3, true);
+        assert_eq!(assignment.values(), &[false, false, false, true]);
+    }
+}
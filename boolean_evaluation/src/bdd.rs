@@ -0,0 +1,695 @@
+//! Reduced ordered binary decision diagrams.
+//!
+//! A [`Bdd`] holds the nodes of any number of diagrams over the same variables. Nodes are
+//! hash-consed through a unique table, so two functions are equal exactly when their
+//! [`BddRef`]s are, whatever formulas they were built from. Which variable is tested first is
+//! up to the order the manager is created with.
+
+use crate::assignment::Assignment;
+use crate::big_uint::BigUint;
+use crate::node::Node;
+use crate::operator::Operator;
+use crate::operator::Operator::*;
+use crate::tree::VariableRef;
+use std::collections::HashMap;
+
+/// Function held by a [`Bdd`], valid for that manager only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BddRef(usize);
+
+impl BddRef {
+    pub const FALSE: BddRef = BddRef(0);
+    pub const TRUE: BddRef = BddRef(1);
+
+    pub fn is_constant(self) -> bool {
+        self.0 < 2
+    }
+}
+
+/// Test of `variable`, going to `high` when it is true and to `low` otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BddNode {
+    variable: usize,
+    low: BddRef,
+    high: BddRef,
+}
+
+/// Manager owning the nodes of diagrams over `variable_count` variables.
+#[derive(Debug, Clone)]
+pub struct Bdd {
+    nodes: Vec<BddNode>,
+    unique: HashMap<(usize, BddRef, BddRef), BddRef>,
+    computed: HashMap<(Operator, BddRef, BddRef), BddRef>,
+    /// Variable tested at each level, from the root down.
+    order: Vec<usize>,
+    /// Level of each variable.
+    level: Vec<usize>,
+    /// Slots of `nodes` no diagram uses any more, reused before the arena grows.
+    free: Vec<usize>,
+}
+
+impl Bdd {
+    /// Manager testing variable 0 first, then variable 1, and so on.
+    pub fn new(variable_count: usize) -> Self {
+        Self::with_order((0..variable_count).collect())
+    }
+
+    /// Manager testing the variables in the order given, the first one at the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `order` holds each variable below its length once.
+    pub fn with_order(order: Vec<usize>) -> Self {
+        let mut level = vec![usize::MAX; order.len()];
+        for (l, &v) in order.iter().enumerate() {
+            if v >= order.len() || level[v] != usize::MAX {
+                panic!("Variable order is not a permutation");
+            }
+            level[v] = l;
+        }
+        let terminal = BddNode {
+            variable: usize::MAX,
+            low: BddRef::FALSE,
+            high: BddRef::FALSE,
+        };
+        Self {
+            nodes: vec![terminal, terminal],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+            order,
+            level,
+            free: vec![],
+        }
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Variables from the root down.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    pub fn constant(value: bool) -> BddRef {
+        if value {
+            BddRef::TRUE
+        } else {
+            BddRef::FALSE
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics unless `variable` is below [`Bdd::variable_count`].
+    pub fn variable(&mut self, variable: usize) -> BddRef {
+        assert!(
+            variable < self.variable_count(),
+            "Variable {} is past the {} variables of the manager",
+            variable,
+            self.variable_count()
+        );
+        self.make(variable, BddRef::FALSE, BddRef::TRUE)
+    }
+
+    // The one node testing `variable` between `low` and `high`, or `low` when they are equal.
+    fn make(&mut self, variable: usize, low: BddRef, high: BddRef) -> BddRef {
+        if low == high {
+            return low;
+        }
+        if let Some(&f) = self.unique.get(&(variable, low, high)) {
+            return f;
+        }
+        let node = BddNode {
+            variable,
+            low,
+            high,
+        };
+        let f = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                BddRef(i)
+            }
+            None => {
+                self.nodes.push(node);
+                BddRef(self.nodes.len() - 1)
+            }
+        };
+        self.unique.insert((variable, low, high), f);
+        f
+    }
+
+    // Level of the variable tested by `f`, the variable count for constants.
+    fn level_of(&self, f: BddRef) -> usize {
+        if f.is_constant() {
+            self.variable_count()
+        } else {
+            self.level[self.nodes[f.0].variable]
+        }
+    }
+
+    // Low and high cofactors of `f` by the variable at `level`, which `f` tests at most first.
+    fn cofactors(&self, f: BddRef, level: usize) -> (BddRef, BddRef) {
+        if self.level_of(f) == level {
+            (self.nodes[f.0].low, self.nodes[f.0].high)
+        } else {
+            (f, f)
+        }
+    }
+
+    /// `op f`, as [`Operator::eval_unary`].
+    pub fn apply_unary(&mut self, op: Operator, f: BddRef) -> BddRef {
+        assert!(op == Not, "{:?} is not a unary operator", op);
+        if f.is_constant() {
+            return Self::constant(f == BddRef::FALSE);
+        }
+        if let Some(&r) = self.computed.get(&(op, f, f)) {
+            return r;
+        }
+        let BddNode {
+            variable,
+            low,
+            high,
+        } = self.nodes[f.0];
+        let low = self.apply_unary(op, low);
+        let high = self.apply_unary(op, high);
+        let r = self.make(variable, low, high);
+        self.computed.insert((op, f, f), r);
+        r
+    }
+
+    /// `f op g`, as [`Operator::eval_binary`].
+    pub fn apply_binary(&mut self, op: Operator, f: BddRef, g: BddRef) -> BddRef {
+        if let Some(r) = terminal_case(op, f, g) {
+            return r;
+        }
+        // every binary operator but `Imply` is symmetric
+        let key = if op != Imply && g < f {
+            (op, g, f)
+        } else {
+            (op, f, g)
+        };
+        if let Some(&r) = self.computed.get(&key) {
+            return r;
+        }
+        let level = self.level_of(f).min(self.level_of(g));
+        let (f_low, f_high) = self.cofactors(f, level);
+        let (g_low, g_high) = self.cofactors(g, level);
+        let low = self.apply_binary(op, f_low, g_low);
+        let high = self.apply_binary(op, f_high, g_high);
+        let r = self.make(self.order[level], low, high);
+        self.computed.insert(key, r);
+        r
+    }
+
+    /// Diagram of `node`, variable `v.index` of the formula being variable `v.index` here.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `node` has a variable past [`Bdd::variable_count`].
+    pub fn compile(&mut self, node: &Node) -> BddRef {
+        match node {
+            Node::Variable(v) => self.variable(v.index),
+            Node::Constant(x) => Self::constant(*x),
+            Node::UnaryExpr { op, child } => {
+                let child = self.compile(child);
+                self.apply_unary(*op, child)
+            }
+            Node::BinaryExpr { op, lhs, rhs } => {
+                let lhs = self.compile(lhs);
+                let rhs = self.compile(rhs);
+                self.apply_binary(*op, lhs, rhs)
+            }
+        }
+    }
+
+    pub fn eval(&self, f: BddRef, assignment: &Assignment) -> bool {
+        let mut f = f;
+        while !f.is_constant() {
+            let node = self.nodes[f.0];
+            f = if assignment.get(node.variable) {
+                node.high
+            } else {
+                node.low
+            };
+        }
+        f == BddRef::TRUE
+    }
+
+    /// Number of assignments of every variable of the manager making `f` true.
+    pub fn count(&self, f: BddRef) -> BigUint {
+        let mut cache = HashMap::new();
+        self.count_below(f, &mut cache) << self.level_of(f)
+    }
+
+    // Models of `f` over the variables from its level down.
+    fn count_below(&self, f: BddRef, cache: &mut HashMap<BddRef, BigUint>) -> BigUint {
+        if f.is_constant() {
+            return if f == BddRef::TRUE {
+                BigUint::one()
+            } else {
+                BigUint::zero()
+            };
+        }
+        if let Some(count) = cache.get(&f) {
+            return count.clone();
+        }
+        let level = self.level_of(f);
+        let BddNode { low, high, .. } = self.nodes[f.0];
+        // variables skipped on the way to a child are free
+        let low_count = self.count_below(low, cache) << (self.level_of(low) - level - 1);
+        let high_count = self.count_below(high, cache) << (self.level_of(high) - level - 1);
+        let count = low_count + high_count;
+        cache.insert(f, count.clone());
+        count
+    }
+
+    /// Number of decision nodes of `f`, constants left out.
+    pub fn size(&self, f: BddRef) -> usize {
+        self.shared_size(&[f])
+    }
+
+    // Number of decision nodes of all of `roots`, shared nodes counted once.
+    fn shared_size(&self, roots: &[BddRef]) -> usize {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = roots.to_vec();
+        let mut size = 0;
+        while let Some(f) = stack.pop() {
+            if f.is_constant() || seen[f.0] {
+                continue;
+            }
+            seen[f.0] = true;
+            size += 1;
+            stack.push(self.nodes[f.0].low);
+            stack.push(self.nodes[f.0].high);
+        }
+        size
+    }
+
+    /// Reorders the variables by Rudell's sifting to make the diagrams of `roots` small: each
+    /// variable in turn, those tested by the most nodes first, is moved through every level by
+    /// swaps of adjacent levels and left where the diagrams were smallest.
+    ///
+    /// Nodes are rewritten in place, so `roots` and the nodes below them keep their meaning.
+    /// Any other function of the manager is dropped and must not be used afterwards.
+    pub fn sift(&mut self, roots: &[BddRef]) {
+        self.keep_only(roots);
+        let mut variables: Vec<usize> = (0..self.variable_count()).collect();
+        let tested_by = |bdd: &Bdd, v: usize| {
+            (2..bdd.nodes.len())
+                .filter(|&i| bdd.nodes[i].variable == v && bdd.is_alive(BddRef(i)))
+                .count()
+        };
+        variables.sort_by_key(|&v| std::cmp::Reverse(tested_by(self, v)));
+        let bottom = self.variable_count().saturating_sub(1);
+        for v in variables {
+            let mut best = (self.shared_size(roots), self.level[v]);
+            while self.level[v] < bottom {
+                self.swap(self.level[v]);
+                best = best.min((self.shared_size(roots), self.level[v]));
+            }
+            while self.level[v] > 0 {
+                self.swap(self.level[v] - 1);
+                best = best.min((self.shared_size(roots), self.level[v]));
+            }
+            while self.level[v] < best.1 {
+                self.swap(self.level[v]);
+            }
+            self.keep_only(roots);
+        }
+    }
+
+    // Exchanges the variables at `level` and `level + 1`. Each node testing the upper variable
+    // `x` with a child testing the lower one `y` is rewritten to test `y` first, with children
+    // testing `x`, and still stands for the same function.
+    fn swap(&mut self, level: usize) {
+        let (x, y) = (self.order[level], self.order[level + 1]);
+        let upper: Vec<usize> = (2..self.nodes.len())
+            .filter(|&i| self.nodes[i].variable == x && self.is_alive(BddRef(i)))
+            .collect();
+        for i in upper {
+            let BddNode { low, high, .. } = self.nodes[i];
+            let (low_low, low_high) = self.cofactors(low, level + 1);
+            let (high_low, high_high) = self.cofactors(high, level + 1);
+            if low_low == low_high && high_low == high_high {
+                // neither child tests `y`
+                continue;
+            }
+            let new_low = self.make(x, low_low, high_low);
+            let new_high = self.make(x, low_high, high_high);
+            // no node testing `y` can have these children, one of them tests `x`
+            self.unique.remove(&(x, low, high));
+            self.unique.insert((y, new_low, new_high), BddRef(i));
+            self.nodes[i] = BddNode {
+                variable: y,
+                low: new_low,
+                high: new_high,
+            };
+        }
+        self.order.swap(level, level + 1);
+        self.level[x] = level + 1;
+        self.level[y] = level;
+    }
+
+    fn is_alive(&self, f: BddRef) -> bool {
+        let BddNode {
+            variable,
+            low,
+            high,
+        } = self.nodes[f.0];
+        self.unique.get(&(variable, low, high)) == Some(&f)
+    }
+
+    // Forgets the nodes unreachable from `roots`, their slots being reused for new nodes.
+    fn keep_only(&mut self, roots: &[BddRef]) {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = roots.to_vec();
+        while let Some(f) = stack.pop() {
+            if !seen[f.0] {
+                seen[f.0] = true;
+                stack.push(self.nodes[f.0].low);
+                stack.push(self.nodes[f.0].high);
+            }
+        }
+        self.unique.retain(|_, f| seen[f.0]);
+        self.computed.clear();
+        self.free = (2..self.nodes.len()).filter(|&i| !seen[i]).collect();
+    }
+
+    /// Formula equal to `f` as nested if-then-else, `x & high | !x & low` for a test of `x`,
+    /// shortened when a branch is constant. Variable `i` is `variables[i]`.
+    pub fn to_node(&self, f: BddRef, variables: &[VariableRef]) -> Node {
+        let mut cache = HashMap::new();
+        self.to_node_cached(f, variables, &mut cache)
+    }
+
+    fn to_node_cached(
+        &self,
+        f: BddRef,
+        variables: &[VariableRef],
+        cache: &mut HashMap<BddRef, Node>,
+    ) -> Node {
+        if f.is_constant() {
+            return Node::Constant(f == BddRef::TRUE);
+        }
+        if let Some(node) = cache.get(&f) {
+            return node.clone();
+        }
+        let BddNode {
+            variable,
+            low,
+            high,
+        } = self.nodes[f.0];
+        let x = Node::Variable(variables[variable].clone());
+        let node = match (low, high) {
+            (BddRef::FALSE, BddRef::TRUE) => x,
+            (BddRef::TRUE, BddRef::FALSE) => !x,
+            (low, BddRef::TRUE) => x | self.to_node_cached(low, variables, cache),
+            (low, BddRef::FALSE) => !x & self.to_node_cached(low, variables, cache),
+            (BddRef::FALSE, high) => x & self.to_node_cached(high, variables, cache),
+            (BddRef::TRUE, high) => !x | self.to_node_cached(high, variables, cache),
+            (low, high) => {
+                let high = self.to_node_cached(high, variables, cache);
+                let low = self.to_node_cached(low, variables, cache);
+                (x.clone() & high) | (!x & low)
+            }
+        };
+        cache.insert(f, node.clone());
+        node
+    }
+}
+
+/// How [`Tree::to_bdd`](crate::tree::Tree::to_bdd) orders the variables of a formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableOrder {
+    /// The index order of [`Tree::variable_list`](crate::tree::Tree::variable_list).
+    Index,
+    /// The order of [`dfs_order`], keeping variables that occur close together in the formula
+    /// close together in the diagram.
+    DepthFirst,
+    /// [`DepthFirst`](VariableOrder::DepthFirst) improved by [`Bdd::sift`].
+    Sifted,
+}
+
+/// Variables in the order of their first occurrence in a left to right, depth-first walk of
+/// `node`, then those of the `variable_count` variables that do not occur.
+pub fn dfs_order(node: &Node, variable_count: usize) -> Vec<usize> {
+    let mut order = vec![];
+    let mut seen = vec![false; variable_count];
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        match node {
+            Node::Variable(v) if !seen[v.index] => {
+                seen[v.index] = true;
+                order.push(v.index);
+            }
+            Node::UnaryExpr { child, .. } => stack.push(child),
+            Node::BinaryExpr { lhs, rhs, .. } => {
+                stack.push(rhs);
+                stack.push(lhs);
+            }
+            _ => {}
+        }
+    }
+    order.extend((0..variable_count).filter(|&v| !seen[v]));
+    order
+}
+
+// Result of `f op g` known without looking into the diagrams.
+fn terminal_case(op: Operator, f: BddRef, g: BddRef) -> Option<BddRef> {
+    if f.is_constant() && g.is_constant() {
+        return Some(Bdd::constant(
+            op.eval_binary(f == BddRef::TRUE, g == BddRef::TRUE),
+        ));
+    }
+    let (zero, one) = (BddRef::FALSE, BddRef::TRUE);
+    match op {
+        And if f == zero || g == zero => Some(zero),
+        And if f == one || f == g => Some(g),
+        And if g == one => Some(f),
+        Or if f == one || g == one => Some(one),
+        Or if f == zero || f == g => Some(g),
+        Or if g == zero => Some(f),
+        Xor if f == g => Some(zero),
+        Xor if f == zero => Some(g),
+        Xor if g == zero => Some(f),
+        Xnor if f == g => Some(one),
+        Xnor if f == one => Some(g),
+        Xnor if g == one => Some(f),
+        Imply if f == zero || g == one || f == g => Some(one),
+        Imply if f == one => Some(g),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod bdd_test {
+    use crate::assignment::Assignment;
+    use crate::bdd::{dfs_order, Bdd, BddRef, VariableOrder};
+    use crate::operator::Operator::*;
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    const FORMULAS: [&str; 8] = [
+        "AB&C|",
+        "AB>C=",
+        "AB|C&D!^",
+        "AB^BC^&CA^&",
+        "ABC||D!&AB&!&",
+        "AB>BC>&AC!&|",
+        "AA!&",
+        "A1|",
+    ];
+
+    #[test]
+    fn test_compile_matches_eval() {
+        for formula in FORMULAS {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let mut bdd = Bdd::new(count);
+            let f = bdd.compile(&tree.root);
+            for bitfield in 0..1u64 << count {
+                let assignment = Assignment::from_bits(bitfield, count);
+                assert_eq!(
+                    bdd.eval(f, &assignment),
+                    tree.root.eval_with(&assignment),
+                    "{}",
+                    formula
+                );
+            }
+            assert_eq!(bdd.count(f), tree.count_models(), "{}", formula);
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut bdd = Bdd::new(2);
+        let a = bdd.variable(0);
+        let b = bdd.variable(1);
+        for op in [And, Or, Xor, Imply, Xnor] {
+            let f = bdd.apply_binary(op, a, b);
+            for bitfield in 0..4 {
+                let assignment = Assignment::from_bits(bitfield, 2);
+                let expected = op.eval_binary(assignment.get(0), assignment.get(1));
+                assert_eq!(bdd.eval(f, &assignment), expected, "{:?}", op);
+            }
+        }
+        let not_a = bdd.apply_unary(Not, a);
+        assert_eq!(bdd.apply_binary(And, a, not_a), BddRef::FALSE);
+        assert_eq!(bdd.apply_binary(Or, a, not_a), BddRef::TRUE);
+        assert_eq!(bdd.apply_unary(Not, not_a), a);
+        assert_eq!(bdd.apply_unary(Not, BddRef::TRUE), BddRef::FALSE);
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut bdd = Bdd::new(3);
+        let mut compile = |formula: &str| bdd.compile(&Tree::from_str(formula).unwrap().root);
+        assert_eq!(compile("AB>"), compile("A!B|"));
+        assert_eq!(compile("AB^!"), compile("AB="));
+        assert_eq!(compile("AB|C&"), compile("AC&BC&|"));
+        assert_eq!(compile("AB&A|"), compile("A"));
+        assert_ne!(compile("AB&"), compile("AB|"));
+        assert_eq!(compile("AA!&"), BddRef::FALSE);
+    }
+
+    #[test]
+    fn test_to_node() {
+        for formula in FORMULAS {
+            let tree = Tree::from_str(formula).unwrap();
+            let variables = tree.variable_list.clone().unwrap_or_default();
+            let mut bdd = Bdd::new(tree.variable_count());
+            let f = bdd.compile(&tree.root);
+            let mut read = tree.clone();
+            read.root = bdd.to_node(f, &variables);
+            assert_eq!(
+                TruthTable::from(read),
+                TruthTable::from(tree),
+                "{}",
+                formula
+            );
+        }
+        let tree = Tree::from_str("AB|").unwrap();
+        let (bdd, f) = tree.to_bdd(VariableOrder::Index);
+        let variables = tree.variable_list.unwrap();
+        assert_eq!(bdd.to_node(f, &variables).to_rpn(), "AB|");
+    }
+
+    #[test]
+    fn test_order() {
+        // pairs A-D, B-E and C-F: interleaving them keeps the diagram linear
+        let tree = Tree::from_str("AD&BE&|CF&|").unwrap();
+        let mut separated = Bdd::new(6);
+        let f = separated.compile(&tree.root);
+        let mut interleaved = Bdd::with_order(vec![0, 3, 1, 4, 2, 5]);
+        let g = interleaved.compile(&tree.root);
+        assert_eq!(separated.size(f), 14);
+        assert_eq!(interleaved.size(g), 6);
+        assert_eq!(separated.count(f), interleaved.count(g));
+        assert_eq!(interleaved.order(), &[0, 3, 1, 4, 2, 5]);
+    }
+
+    #[test]
+    fn test_dfs_order() {
+        let tree = Tree::from_str("AD&BE&|CF&|").unwrap();
+        assert_eq!(dfs_order(&tree.root, 6), vec![0, 3, 1, 4, 2, 5]);
+        let (bdd, f) = tree.to_bdd(VariableOrder::DepthFirst);
+        assert_eq!(bdd.size(f), 6);
+        let tree = Tree::from_str("CA!&").unwrap();
+        assert_eq!(dfs_order(&tree.root, 4), vec![1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_sift() {
+        // a multiplexer choosing among D, E, F and G by A and B, with its data inputs pairs,
+        // starting with the data inputs above the selectors
+        let tree = Tree::from_str("DE&A!B!&&FG|A!B&&|DG^AB!&&|EF=AB&&|").unwrap();
+        let mut bdd = Bdd::with_order(vec![2, 3, 4, 5, 0, 1]);
+        let f = bdd.compile(&tree.root);
+        let (a, e) = (bdd.variable(0), bdd.variable(3));
+        let g = bdd.apply_binary(Or, e, a);
+        let (count_f, count_g) = (bdd.count(f), bdd.count(g));
+        let before = bdd.size(f);
+        bdd.sift(&[f, g]);
+        assert!(bdd.size(f) < before, "{} {}", bdd.size(f), before);
+        assert_eq!(bdd.count(f), count_f);
+        assert_eq!(bdd.count(g), count_g);
+        let count = tree.variable_count();
+        for bitfield in 0..1u64 << count {
+            let assignment = Assignment::from_bits(bitfield, count);
+            assert_eq!(bdd.eval(f, &assignment), tree.root.eval_with(&assignment));
+            assert_eq!(
+                bdd.eval(g, &assignment),
+                assignment.get(3) | assignment.get(0)
+            );
+        }
+        // the diagrams still share their nodes with newly built ones
+        let h = bdd.compile(&tree.root);
+        assert_eq!(h, f);
+
+        // from all of A, B, C above all of D, E, F, sifting finds the interleaved order of the
+        // pairs
+        let tree = Tree::from_str("AD&BE&|CF&|").unwrap();
+        let mut bdd = Bdd::new(6);
+        let f = bdd.compile(&tree.root);
+        assert_eq!(bdd.size(f), 14);
+        bdd.sift(&[f]);
+        assert_eq!(bdd.size(f), 6);
+        let level = |v: usize| bdd.order().iter().position(|&w| w == v).unwrap();
+        for (x, y) in [(0, 3), (1, 4), (2, 5)] {
+            assert_eq!(level(x).abs_diff(level(y)), 1, "{:?}", bdd.order());
+        }
+    }
+
+    #[test]
+    fn test_to_bdd_sifted() {
+        // an enable over all of A, B, C first, so that the depth-first order keeps the pairs
+        // (A, D), (B, E) and (C, F) apart
+        let tree = Tree::from_str("ABC||AD&BE&|CF&|&").unwrap();
+        let (depth_first, g) = tree.to_bdd(VariableOrder::DepthFirst);
+        assert_eq!(depth_first.order(), &[0, 1, 2, 3, 4, 5]);
+        let (bdd, f) = tree.to_bdd(VariableOrder::Sifted);
+        assert!(
+            bdd.size(f) < depth_first.size(g),
+            "{} {}",
+            bdd.size(f),
+            depth_first.size(g)
+        );
+        assert_eq!(bdd.count(f), depth_first.count(g));
+        let count = tree.variable_count();
+        for bitfield in 0..1u64 << count {
+            let assignment = Assignment::from_bits(bitfield, count);
+            assert_eq!(bdd.eval(f, &assignment), tree.root.eval_with(&assignment));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_order_not_permutation() {
+        Bdd::with_order(vec![0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "past the 2 variables")]
+    fn test_variable_out_of_range() {
+        let tree = Tree::from_str("AB&C|").unwrap();
+        Bdd::new(2).compile(&tree.root);
+    }
+
+    #[test]
+    fn test_sift_reuses_nodes() {
+        let tree = Tree::from_str("AD&BE&|CF&|").unwrap();
+        let mut bdd = Bdd::new(6);
+        let f = bdd.compile(&tree.root);
+        bdd.sift(&[f]);
+        let arena = bdd.nodes.len();
+        let count = bdd.count(f);
+        for _ in 0..10 {
+            bdd.sift(&[f]);
+        }
+        assert_eq!(bdd.nodes.len(), arena);
+        assert_eq!(bdd.count(f), count);
+        assert_eq!(bdd.size(f), 6);
+    }
+}
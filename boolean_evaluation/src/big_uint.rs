@@ -0,0 +1,196 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops;
+
+/// Unsigned integer of any size, as needed to count the models of formulas with more variables
+/// than fit in a machine word.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    // base 2^32 digits, least significant first, without trailing zeros
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: vec![] }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// `2^exponent`
+    pub fn power_of_two(exponent: usize) -> Self {
+        Self::one() << exponent
+    }
+
+    /// Closest `f64`, infinite past `f64::MAX`.
+    pub fn to_f64(&self) -> f64 {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4294967296.0 + limb as f64)
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    // Divides in place by a single digit, returning the remainder.
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let current = (remainder << 32) | *limb as u64;
+            *limb = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        remainder as u32
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        }
+        .normalize()
+    }
+}
+
+impl ops::Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let (long, short) = if self.limbs.len() >= rhs.limbs.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        let mut carry = 0u64;
+        for (i, &limb) in long.limbs.iter().enumerate() {
+            let sum = limb as u64 + *short.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl ops::Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: BigUint) -> BigUint {
+        &self + &rhs
+    }
+}
+
+impl ops::Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::zero();
+        }
+        let mut limbs = vec![0u32; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let current = limbs[i + j] as u64 + a as u64 * b as u64 + carry;
+                limbs[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u32;
+        }
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl ops::Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: BigUint) -> BigUint {
+        &self * &rhs
+    }
+}
+
+impl ops::Shl<usize> for BigUint {
+    type Output = BigUint;
+
+    fn shl(self, shift: usize) -> BigUint {
+        if self.is_zero() {
+            return self;
+        }
+        let (words, bits) = (shift / 32, shift % 32);
+        let mut limbs = vec![0u32; words];
+        let mut carry = 0u32;
+        for limb in self.limbs {
+            limbs.push((limb << bits) | carry);
+            carry = if bits == 0 { 0 } else { limb >> (32 - bits) };
+        }
+        limbs.push(carry);
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // peel off nine decimal digits at a time
+        let mut rest = self.clone();
+        let mut chunks = vec![];
+        while !rest.is_zero() {
+            chunks.push(rest.div_rem_small(1_000_000_000));
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod big_uint_test {
+    use crate::big_uint::BigUint;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(
+            BigUint::power_of_two(100).to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(
+            (BigUint::from(1_000_000_000) << 40).to_string(),
+            "1099511627776000000000"
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(&max + &BigUint::one(), BigUint::power_of_two(64));
+        assert_eq!(
+            (&max * &max).to_string(),
+            "340282366920938463426481119284349108225"
+        );
+        assert_eq!(BigUint::zero() * max.clone(), BigUint::zero());
+        assert_eq!(BigUint::from(3) << 0, BigUint::from(3));
+        assert_eq!(BigUint::power_of_two(80).to_f64(), 2f64.powi(80));
+    }
+}
@@ -0,0 +1,592 @@
+use crate::assignment::Assignment;
+use crate::literal::{Clause, Literal};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+const VARIABLE_DECAY: f64 = 0.95;
+const CLAUSE_DECAY: f64 = 0.999;
+const RESTART_BASE: u64 = 100;
+
+/// Decides a set of clauses with a conflict-driven clause-learning solver: two watched literals
+/// per clause for propagation, first-UIP learning with non-chronological backjumping, VSIDS
+/// variable activities with phase saving, Luby restarts and periodic deletion of the least
+/// active learned clauses.
+///
+/// Takes the same input and gives the same answer as [`dpll::solve`](crate::dpll::solve).
+pub fn solve(clauses: &[Clause], variable_count: usize) -> Option<Assignment> {
+    Cdcl::with_clauses(clauses, variable_count).solve()
+}
+
+// Literals are coded as `2 * variable + negated` to index watch lists directly.
+type Lit = usize;
+
+fn code(literal: Literal) -> Lit {
+    literal.variable * 2 + literal.negated as usize
+}
+
+fn var(lit: Lit) -> usize {
+    lit / 2
+}
+
+fn negated(lit: Lit) -> bool {
+    lit & 1 == 1
+}
+
+struct ClauseData {
+    lits: Vec<Lit>,
+    learnt: bool,
+    activity: f64,
+}
+
+// Max-heap entry for the decision order, stale entries are skipped when popped.
+struct Candidate(f64, usize);
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// Incremental solver: clauses may be added between searches, keeping what was learned.
+pub(crate) struct Cdcl {
+    clauses: Vec<ClauseData>,
+    // clauses to visit when the literal becomes false
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    // trail length at the start of each decision level
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    activity: Vec<f64>,
+    variable_increment: f64,
+    clause_increment: f64,
+    order: BinaryHeap<Candidate>,
+    saved_phase: Vec<bool>,
+    seen: Vec<bool>,
+    max_learnts: f64,
+    // set once the clauses are known unsatisfiable, whatever is added later
+    conflicting: bool,
+}
+
+impl Cdcl {
+    fn new(variable_count: usize) -> Self {
+        Self {
+            clauses: vec![],
+            watches: vec![vec![]; variable_count * 2],
+            values: vec![None; variable_count],
+            levels: vec![0; variable_count],
+            reasons: vec![None; variable_count],
+            trail: Vec::with_capacity(variable_count),
+            trail_limits: vec![],
+            propagated: 0,
+            activity: vec![0.0; variable_count],
+            variable_increment: 1.0,
+            clause_increment: 1.0,
+            order: (0..variable_count).map(|v| Candidate(0.0, v)).collect(),
+            saved_phase: vec![true; variable_count],
+            seen: vec![false; variable_count],
+            max_learnts: 0.0,
+            conflicting: false,
+        }
+    }
+
+    /// Solver over `clauses`, which may mention variables past `variable_count`.
+    pub(crate) fn with_clauses(clauses: &[Clause], variable_count: usize) -> Self {
+        let variable_count = clauses
+            .iter()
+            .flatten()
+            .map(|l| l.variable + 1)
+            .fold(variable_count, usize::max);
+        let mut solver = Self::new(variable_count);
+        for clause in clauses {
+            if !solver.add_clause(clause) {
+                break;
+            }
+        }
+        solver
+    }
+
+    /// Searches for a model of the clauses added so far.
+    pub(crate) fn solve(&mut self) -> Option<Assignment> {
+        if self.conflicting {
+            return None;
+        }
+        if !self.search() {
+            self.conflicting = true;
+            return None;
+        }
+        let model = self.values.iter().map(|v| v.unwrap_or(false)).collect();
+        self.backtrack(0);
+        Some(model)
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[var(lit)].map(|v| v != negated(lit))
+    }
+
+    fn level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    /// Adds a clause over the variables of the solver, returns false when the clauses are
+    /// already unsatisfiable.
+    pub(crate) fn add_clause(&mut self, clause: &Clause) -> bool {
+        if self.conflicting {
+            return false;
+        }
+        let mut lits: Vec<Lit> = clause.iter().map(|&l| code(l)).collect();
+        lits.sort_unstable();
+        lits.dedup();
+        if lits.windows(2).any(|w| var(w[0]) == var(w[1])) {
+            return true;
+        }
+        lits.retain(|&l| self.value(l) != Some(false));
+        if lits.iter().any(|&l| self.value(l) == Some(true)) {
+            return true;
+        }
+        match lits.len() {
+            0 => {
+                self.conflicting = true;
+                false
+            }
+            1 => {
+                self.enqueue(lits[0], None);
+                self.conflicting = self.propagate().is_some();
+                !self.conflicting
+            }
+            _ => {
+                self.attach(ClauseData {
+                    lits,
+                    learnt: false,
+                    activity: 0.0,
+                });
+                true
+            }
+        }
+    }
+
+    fn attach(&mut self, clause: ClauseData) -> usize {
+        let index = self.clauses.len();
+        self.watches[clause.lits[0]].push(index);
+        self.watches[clause.lits[1]].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let v = var(lit);
+        self.values[v] = Some(!negated(lit));
+        self.levels[v] = self.level();
+        self.reasons[v] = reason;
+        self.trail.push(lit);
+    }
+
+    // Two watched literals: a clause is only visited when one of its first two literals becomes
+    // false, and then either finds a replacement watch, becomes unit or is in conflict.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let false_lit = self.trail[self.propagated] ^ 1;
+            self.propagated += 1;
+            let watchers = std::mem::take(&mut self.watches[false_lit]);
+            let mut kept = Vec::with_capacity(watchers.len());
+            let mut conflict = None;
+
+            for (i, &index) in watchers.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watchers[i..]);
+                    break;
+                }
+                let lits = &mut self.clauses[index].lits;
+                if lits[0] == false_lit {
+                    lits.swap(0, 1);
+                }
+                let first = lits[0];
+                if self.values[var(first)].map(|v| v != negated(first)) == Some(true) {
+                    kept.push(index);
+                    continue;
+                }
+                let replacement = (2..lits.len()).find(|&k| {
+                    self.values[var(lits[k])].map(|v| v != negated(lits[k])) != Some(false)
+                });
+                if let Some(k) = replacement {
+                    lits.swap(1, k);
+                    let watch = lits[1];
+                    self.watches[watch].push(index);
+                    continue;
+                }
+                kept.push(index);
+                match self.value(first) {
+                    Some(false) => conflict = Some(index),
+                    _ => self.enqueue(first, Some(index)),
+                }
+            }
+            self.watches[false_lit] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // First unique implication point: resolve the conflict with the reasons of the current level
+    // until a single literal of that level is left. Returns the learned clause, asserting literal
+    // first, and the level to jump back to.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut learnt = vec![0];
+        let mut pending = 0;
+        let mut index = self.trail.len();
+        let mut clause = conflict;
+        let mut asserting = None;
+
+        loop {
+            self.bump_clause(clause);
+            let skip = usize::from(asserting.is_some());
+            for k in skip..self.clauses[clause].lits.len() {
+                let q = self.clauses[clause].lits[k];
+                let v = var(q);
+                if !self.seen[v] && self.levels[v] > 0 {
+                    self.seen[v] = true;
+                    self.bump_variable(v);
+                    if self.levels[v] == self.level() {
+                        pending += 1;
+                    } else {
+                        learnt.push(q);
+                    }
+                }
+            }
+            loop {
+                index -= 1;
+                if self.seen[var(self.trail[index])] {
+                    break;
+                }
+            }
+            let p = self.trail[index];
+            self.seen[var(p)] = false;
+            pending -= 1;
+            asserting = Some(p);
+            if pending == 0 {
+                break;
+            }
+            clause = self.reasons[var(p)].unwrap();
+        }
+        learnt[0] = asserting.unwrap() ^ 1;
+        for &lit in &learnt[1..] {
+            self.seen[var(lit)] = false;
+        }
+
+        let mut backjump = 0;
+        if learnt.len() > 1 {
+            let (k, _) = learnt
+                .iter()
+                .enumerate()
+                .skip(1)
+                .max_by_key(|(_, &l)| self.levels[var(l)])
+                .unwrap();
+            learnt.swap(1, k);
+            backjump = self.levels[var(learnt[1])];
+        }
+        (learnt, backjump)
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..) {
+            let v = var(lit);
+            self.saved_phase[v] = !negated(lit);
+            self.values[v] = None;
+            self.reasons[v] = None;
+            self.order.push(Candidate(self.activity[v], v));
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = limit;
+    }
+
+    fn bump_variable(&mut self, v: usize) {
+        self.activity[v] += self.variable_increment;
+        if self.activity[v] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.variable_increment *= 1e-100;
+            self.order = (0..self.values.len())
+                .filter(|&v| self.values[v].is_none())
+                .map(|v| Candidate(self.activity[v], v))
+                .collect();
+        }
+        if self.values[v].is_none() {
+            self.order.push(Candidate(self.activity[v], v));
+        }
+    }
+
+    fn bump_clause(&mut self, index: usize) {
+        if !self.clauses[index].learnt {
+            return;
+        }
+        self.clauses[index].activity += self.clause_increment;
+        if self.clauses[index].activity > 1e20 {
+            for clause in self.clauses.iter_mut().filter(|c| c.learnt) {
+                clause.activity *= 1e-20;
+            }
+            self.clause_increment *= 1e-20;
+        }
+    }
+
+    fn pick_branch(&mut self) -> Option<Lit> {
+        while let Some(Candidate(activity, v)) = self.order.pop() {
+            if self.values[v].is_none() && activity == self.activity[v] {
+                return Some(v * 2 + usize::from(!self.saved_phase[v]));
+            }
+        }
+        None
+    }
+
+    // Keeps the more active half of the learned clauses, only called at level 0 where no
+    // reason is ever looked at again.
+    fn reduce_learnts(&mut self) {
+        let mut activities: Vec<f64> = self
+            .clauses
+            .iter()
+            .filter(|c| c.learnt && c.lits.len() > 2)
+            .map(|c| c.activity)
+            .collect();
+        activities.sort_unstable_by(f64::total_cmp);
+        let Some(&threshold) = activities.get(activities.len() / 2) else {
+            return;
+        };
+        self.clauses
+            .retain(|c| !c.learnt || c.lits.len() <= 2 || c.activity >= threshold);
+        self.reasons.iter_mut().for_each(|r| *r = None);
+        self.watches.iter_mut().for_each(Vec::clear);
+        for (index, clause) in self.clauses.iter().enumerate() {
+            self.watches[clause.lits[0]].push(index);
+            self.watches[clause.lits[1]].push(index);
+        }
+    }
+
+    fn search(&mut self) -> bool {
+        if self.propagate().is_some() {
+            return false;
+        }
+        self.max_learnts = (self.clauses.len() as f64 / 3.0).max(100.0);
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        let mut restart_limit = luby(restarts) * RESTART_BASE;
+
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.level() == 0 {
+                    return false;
+                }
+                conflicts += 1;
+                let (learnt, backjump) = self.analyze(conflict);
+                self.backtrack(backjump);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let index = self.attach(ClauseData {
+                        lits: learnt,
+                        learnt: true,
+                        activity: 0.0,
+                    });
+                    self.bump_clause(index);
+                    self.enqueue(asserting, Some(index));
+                }
+                self.variable_increment /= VARIABLE_DECAY;
+                self.clause_increment /= CLAUSE_DECAY;
+            } else if conflicts >= restart_limit {
+                self.backtrack(0);
+                let learnts = self.clauses.iter().filter(|c| c.learnt).count();
+                if learnts as f64 >= self.max_learnts {
+                    self.reduce_learnts();
+                    self.max_learnts *= 1.1;
+                }
+                restarts += 1;
+                conflicts = 0;
+                restart_limit = luby(restarts) * RESTART_BASE;
+            } else {
+                match self.pick_branch() {
+                    None => return true,
+                    Some(lit) => {
+                        self.trail_limits.push(self.trail.len());
+                        self.enqueue(lit, None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ... scaling the conflicts allowed between restarts.
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut power = 1;
+    while size < i + 1 {
+        size = 2 * size + 1;
+        power *= 2;
+    }
+    while size - 1 != i {
+        size = (size - 1) / 2;
+        power /= 2;
+        i %= size;
+    }
+    power
+}
+
+#[cfg(test)]
+mod cdcl_test {
+    use crate::assignment::Assignment;
+    use crate::cdcl::{luby, solve, Cdcl};
+    use crate::cnf::{cnf_clauses, node_to_cnf};
+    use crate::dpll;
+    use crate::literal::{Clause, Literal};
+    use crate::tree::Tree;
+    use std::str::FromStr;
+
+    fn satisfies(model: &Assignment, clauses: &[Clause]) -> bool {
+        clauses
+            .iter()
+            .all(|c| c.iter().any(|l| l.eval(model.get(l.variable))))
+    }
+
+    // Deterministic 3-SAT instances from a linear congruential generator.
+    fn random_3sat(seed: u64, variables: usize, clauses: usize) -> Vec<Clause> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+        (0..clauses)
+            .map(|_| {
+                (0..3)
+                    .map(|_| Literal {
+                        variable: next() % variables,
+                        negated: next() % 2 == 1,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn pigeonhole(pigeons: usize, holes: usize) -> Vec<Clause> {
+        let var = |p: usize, h: usize| p * holes + h;
+        let mut clauses: Vec<Clause> = (0..pigeons)
+            .map(|p| (0..holes).map(|h| Literal::positive(var(p, h))).collect())
+            .collect();
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![
+                        Literal::negative(var(p, h)),
+                        Literal::negative(var(q, h)),
+                    ]);
+                }
+            }
+        }
+        clauses
+    }
+
+    #[test]
+    fn test_luby() {
+        let sequence: Vec<u64> = (0..15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_cdcl_matches_brute_force() {
+        let formulas = [
+            "AB|",
+            "AA!&",
+            "AA^",
+            "AB>BC>&AC!&&",
+            "AB^BC^&CA^&",
+            "AB|A!B|&AB!|&A!B!|&",
+            "ABC||D!&AB&!&",
+            "1",
+            "0",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let expected = (0..1u64 << count)
+                .any(|bitfield| tree.root.eval_with(&Assignment::from_bits(bitfield, count)));
+            let model = solve(&cnf_clauses(&node_to_cnf(tree.root.clone())), count);
+            assert_eq!(model.is_some(), expected, "{}", formula);
+            if let Some(model) = model {
+                assert!(tree.root.eval_with(&model), "{}", formula);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cdcl_matches_dpll() {
+        for seed in 0..40 {
+            let clauses = random_3sat(seed, 30, 128);
+            let model = solve(&clauses, 30);
+            assert_eq!(model.is_some(), dpll::solve(&clauses, 30).is_some());
+            if let Some(model) = model {
+                assert!(satisfies(&model, &clauses));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cdcl_pigeonhole() {
+        assert!(solve(&pigeonhole(6, 5), 30).is_none());
+        let model = solve(&pigeonhole(5, 5), 25).unwrap();
+        assert!(satisfies(&model, &pigeonhole(5, 5)));
+    }
+
+    #[test]
+    fn test_cdcl_incremental() {
+        // enumerating the models by blocking each in turn counts them all
+        for seed in 0..10 {
+            let clauses = random_3sat(seed, 8, 20);
+            let expected = (0..1u64 << 8)
+                .filter(|&bitfield| satisfies(&Assignment::from_bits(bitfield, 8), &clauses))
+                .count();
+            let mut solver = Cdcl::with_clauses(&clauses, 8);
+            let mut count = 0;
+            while let Some(model) = solver.solve() {
+                assert!(satisfies(&model, &clauses));
+                count += 1;
+                let blocking: Clause = (0..8)
+                    .map(|v| Literal {
+                        variable: v,
+                        negated: model.get(v),
+                    })
+                    .collect();
+                solver.add_clause(&blocking);
+            }
+            assert_eq!(count, expected);
+        }
+    }
+
+    #[test]
+    fn test_cdcl_large() {
+        let clauses = random_3sat(7, 200, 600);
+        let model = solve(&clauses, 200).unwrap();
+        assert!(satisfies(&model, &clauses));
+    }
+}
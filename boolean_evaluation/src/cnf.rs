@@ -1,114 +1,164 @@
-use crate::nnf::node_to_nnf;
+use crate::literal::{canonical_terms, terms_to_node, Clause, Literal};
 use crate::node::Node;
+use crate::normal_form::{collect_terms, normal_form};
 use crate::operator::Operator;
+use crate::tree::VariableRef;
 use Node::*;
 use Operator::*;
 
 pub fn node_to_cnf(node: Node) -> Node {
-    let node = node_to_nnf(node);
-    if let BinaryExpr { op, lhs, rhs } = node {
-        match op {
-            And => BinaryExpr {
-                op: And,
-                lhs: Box::new(node_to_cnf(*lhs)),
-                rhs: Box::new(node_to_cnf(*rhs)),
-            },
-            Or => {
-                let has_and = (
-                    matches!(*lhs, BinaryExpr { op: And, .. }),
-                    matches!(*rhs, BinaryExpr { op: And, .. }),
-                );
+    normal_form(node, And, Or)
+}
 
-                let lhs = node_to_cnf(*lhs);
-                let rhs = node_to_cnf(*rhs);
-                match has_and {
-                    (true, true) => distribute_both(lhs | rhs),
-                    (true, false) => distribute_to_left(lhs | rhs),
-                    (false, true) => distribute_to_right(lhs | rhs),
-                    _ => lhs | rhs,
-                }
-            }
-            _ => unreachable!(),
-        }
-    } else {
-        node
-    }
+/// Collects the clauses of a formula in conjunctive normal form, such as the output of
+/// [`node_to_cnf`]. Clauses made true by a `1` are dropped and `0` literals are left out.
+pub fn cnf_clauses(node: &Node) -> Vec<Clause> {
+    collect_terms(node, And, Or)
 }
 
-// Distribute conjunctions over disjunctions.
-fn distribute_both(node: Node) -> Node {
-    if let BinaryExpr {
-        op: Operator::Or,
-        lhs,
-        rhs,
-    } = node
-    {
-        match (*lhs, *rhs) {
-            (
-                BinaryExpr {
-                    op: And,
-                    lhs: lhs_lhs,
-                    rhs: lhs_rhs,
-                },
-                BinaryExpr {
-                    op: And,
-                    lhs: rhs_lhs,
-                    rhs: rhs_rhs,
-                },
-            ) => {
-                let lhs_lhs = node_to_cnf(*lhs_lhs);
-                let lhs_rhs = node_to_cnf(*lhs_rhs);
-                let rhs_lhs = node_to_cnf(*rhs_lhs);
-                let rhs_rhs = node_to_cnf(*rhs_rhs);
-                // ((ll | rl) & (ll | rr)) & ((lr | rl) & (lr | rr))
-                ((lhs_lhs.clone() | rhs_lhs.clone()) & (lhs_lhs | rhs_rhs.clone()))
-                    & ((lhs_rhs.clone() | rhs_lhs) & (lhs_rhs | rhs_rhs))
-            }
-            _ => unreachable!(),
+/// Formula in conjunctive normal form as a flat set of clauses, kept canonical: literals
+/// sorted and distinct within each clause, no always true clause, no clause containing another,
+/// and clauses sorted. Equal formulas built differently, such as `AB|C|` and `ABC||`, give equal
+/// values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Cnf {
+    pub clauses: Vec<Clause>,
+}
+
+impl Cnf {
+    pub fn new(clauses: Vec<Clause>) -> Self {
+        Self {
+            clauses: canonical_terms(clauses),
         }
-    } else {
-        unreachable!()
+    }
+
+    /// Conjunction of right-nested disjunctions, `1` without clauses and `0` for an empty
+    /// clause. Literal `i` is variable `variables[i]`.
+    pub fn to_node(&self, variables: &[VariableRef]) -> Node {
+        terms_to_node(&self.clauses, variables, Or, And)
     }
 }
 
-fn distribute_to_left(node: Node) -> Node {
-    if let BinaryExpr {
-        op: Operator::Or,
-        lhs,
-        rhs,
-    } = node
-    {
-        match *lhs {
-            BinaryExpr {
-                op: And,
-                lhs: lhs_lhs,
-                rhs: lhs_rhs,
-            } => node_to_cnf(*lhs_lhs | *rhs.clone()) & node_to_cnf(*lhs_rhs | *rhs),
-            _ => unreachable!(),
-        }
-    } else {
-        unreachable!()
+impl From<&Node> for Cnf {
+    /// Clauses of [`node_to_cnf`], equivalent to `node`.
+    fn from(node: &Node) -> Self {
+        Self::new(cnf_clauses(&node_to_cnf(node.clone())))
     }
 }
 
-fn distribute_to_right(node: Node) -> Node {
-    if let BinaryExpr { op: Or, lhs, rhs } = node {
-        match *rhs {
-            BinaryExpr {
-                op: And,
-                lhs: rhs_lhs,
-                rhs: rhs_rhs,
-            } => node_to_cnf(*lhs.clone() | *rhs_lhs) & node_to_cnf(*lhs | *rhs_rhs),
-            _ => unreachable!(),
+/// How [`encode`] turns a formula into clauses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The clauses of [`node_to_cnf`], equivalent to the formula but exponentially many in the
+    /// worst case, as for `(A & B) | (C & D) | (E & F) | ...`.
+    Distributive,
+    /// One auxiliary variable per binary operator, equivalent to the operator applied to its
+    /// operands. Linear in the size of the formula, and each model of the formula extends to
+    /// exactly one model of the clauses, so counts are preserved.
+    Tseitin,
+    /// Tseitin keeping only the implication each subformula needs given whether it occurs
+    /// positively, negatively or both. Fewer clauses, satisfiable exactly when the formula is,
+    /// but a model of the formula can extend to several models of the clauses.
+    PlaistedGreenbaum,
+}
+
+/// Clauses of a formula over `variable_count` variables. The auxiliary variables of the Tseitin
+/// encodings are numbered from `variable_count` on.
+pub fn encode(node: &Node, variable_count: usize, encoding: Encoding) -> Vec<Clause> {
+    let both_polarities = match encoding {
+        Encoding::Distributive => return cnf_clauses(&node_to_cnf(node.clone())),
+        Encoding::Tseitin => true,
+        Encoding::PlaistedGreenbaum => false,
+    };
+    let mut tseitin = Tseitin {
+        clauses: vec![],
+        next_variable: variable_count,
+    };
+    let root = tseitin.literal(node, true, both_polarities);
+    tseitin.clauses.push(vec![root]);
+    tseitin.clauses
+}
+
+struct Tseitin {
+    clauses: Vec<Clause>,
+    next_variable: usize,
+}
+
+impl Tseitin {
+    fn fresh(&mut self) -> Literal {
+        self.next_variable += 1;
+        Literal::positive(self.next_variable - 1)
+    }
+
+    // Literal standing for `node`: it implies `node` when `positive` and is implied by it when
+    // `negative`.
+    fn literal(&mut self, node: &Node, positive: bool, negative: bool) -> Literal {
+        match node {
+            Variable(v) => Literal::positive(v.index),
+            Constant(x) => {
+                let literal = self.fresh();
+                self.clauses.push(vec![if *x { literal } else { !literal }]);
+                literal
+            }
+            UnaryExpr { child, .. } => !self.literal(child, negative, positive),
+            BinaryExpr { op, lhs, rhs } => {
+                let (a, b) = match op {
+                    And | Or => (
+                        self.literal(lhs, positive, negative),
+                        self.literal(rhs, positive, negative),
+                    ),
+                    Imply => (
+                        self.literal(lhs, negative, positive),
+                        self.literal(rhs, positive, negative),
+                    ),
+                    Xor | Xnor => (self.literal(lhs, true, true), self.literal(rhs, true, true)),
+                    Not => unreachable!(),
+                };
+                // clauses of `x -> op(a, b)` without `!x`, and of `op(a, b) -> x` without `x`
+                let (implies, implied): (Vec<Clause>, Vec<Clause>) = match op {
+                    And => (vec![vec![a], vec![b]], vec![vec![!a, !b]]),
+                    Or => (vec![vec![a, b]], vec![vec![!a], vec![!b]]),
+                    Xor => (
+                        vec![vec![a, b], vec![!a, !b]],
+                        vec![vec![!a, b], vec![a, !b]],
+                    ),
+                    Xnor => (
+                        vec![vec![!a, b], vec![a, !b]],
+                        vec![vec![a, b], vec![!a, !b]],
+                    ),
+                    Imply => (vec![vec![!a, b]], vec![vec![a], vec![!b]]),
+                    Not => unreachable!(),
+                };
+                let x = self.fresh();
+                if positive {
+                    for mut clause in implies {
+                        clause.insert(0, !x);
+                        self.clauses.push(clause);
+                    }
+                }
+                if negative {
+                    for mut clause in implied {
+                        clause.insert(0, x);
+                        self.clauses.push(clause);
+                    }
+                }
+                x
+            }
         }
-    } else {
-        unreachable!()
     }
 }
 
 #[cfg(test)]
 mod cnf_test {
-    use crate::cnf::{distribute_both, distribute_to_left, distribute_to_right, node_to_cnf};
+    use crate::assignment::Assignment;
+    use crate::cnf::{cnf_clauses, encode, node_to_cnf, Cnf, Encoding};
+    use crate::count::count_models;
+    use crate::dpll;
+    use crate::literal::Literal;
+    use crate::node::Node;
+    use crate::node::Node::*;
+    use crate::normal_form::{distribute_both, distribute_to_left, distribute_to_right};
+    use crate::operator::Operator::*;
     use crate::tree::Tree;
     use crate::truth_table::TruthTable;
     use std::str::FromStr;
@@ -125,23 +175,193 @@ mod cnf_test {
     fn basic_distribute_both() {
         let node = Tree::from_str("AB&CD&|").unwrap();
         let result = Tree::from_str("AC|AD|&BC|BD|&&").unwrap();
-        assert_eq!(distribute_both(node.root.clone()), result.root);
+        assert_eq!(distribute_both(node.root.clone(), And, Or), result.root);
         assert_eq!(TruthTable::from(node), TruthTable::from(result));
     }
 
+    // Whether `node` is a conjunction of disjunctions of literals.
+    fn is_cnf(node: &Node) -> bool {
+        fn is_clause(node: &Node) -> bool {
+            match node {
+                BinaryExpr { op: Or, lhs, rhs } => is_clause(lhs) && is_clause(rhs),
+                UnaryExpr { child, .. } => matches!(**child, Variable(_) | Constant(_)),
+                BinaryExpr { .. } => false,
+                _ => true,
+            }
+        }
+        match node {
+            BinaryExpr { op: And, lhs, rhs } => is_cnf(lhs) && is_cnf(rhs),
+            _ => is_clause(node),
+        }
+    }
+
+    #[test]
+    fn nested_node_to_cnf() {
+        // (A & B) | (C & (D | E & F)): the right operand only turns into a conjunction once
+        // converted, and distributing over it leaves disjunctions of conjunctions to distribute
+        for formula in ["AB&CDEF&|&|", "AB&C|D|", "ABC&|DE&|"] {
+            let node = Tree::from_str(formula).unwrap();
+            let result = node_to_cnf(node.root.clone());
+            assert!(is_cnf(&result), "{} gave {}", formula, result.to_rpn());
+            let mut converted = node.clone();
+            converted.root = result;
+            assert_eq!(TruthTable::from(converted), TruthTable::from(node), "{}", formula);
+        }
+    }
+
     #[test]
     fn basic_distribute_left() {
         let node = Tree::from_str("AB&C|").unwrap();
         let result = Tree::from_str("AC|BC|&").unwrap();
-        assert_eq!(distribute_to_left(node.root.clone()), result.root);
+        assert_eq!(distribute_to_left(node.root.clone(), And, Or), result.root);
         assert_eq!(TruthTable::from(node), TruthTable::from(result));
     }
 
+    #[test]
+    fn nested_or_to_cnf() {
+        let node = Tree::from_str("AB&C|D|").unwrap();
+        let result = node_to_cnf(node.root.clone());
+        assert_eq!(result, Tree::from_str("AC|D|BC|D|&").unwrap().root);
+        assert_eq!(
+            cnf_clauses(&result),
+            vec![
+                vec![
+                    Literal::positive(0),
+                    Literal::positive(2),
+                    Literal::positive(3)
+                ],
+                vec![
+                    Literal::positive(1),
+                    Literal::positive(2),
+                    Literal::positive(3)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_distribute_both() {
+        let node = Tree::from_str("AB&CD&|EF&|").unwrap();
+        let result = node_to_cnf(node.root.clone());
+        assert_eq!(cnf_clauses(&result).len(), 8);
+        assert!(cnf_clauses(&result).iter().all(|c| c.len() == 3));
+    }
+
+    #[test]
+    fn constant_clauses() {
+        let node = Tree::from_str("A1|B0|&").unwrap();
+        assert_eq!(
+            cnf_clauses(&node_to_cnf(node.root)),
+            vec![vec![Literal::positive(1)]]
+        );
+        let node = Tree::from_str("0").unwrap();
+        assert_eq!(cnf_clauses(&node_to_cnf(node.root)), vec![vec![]]);
+    }
+
     #[test]
     fn basic_distribute_right() {
         let node = Tree::from_str("CAB&|").unwrap();
         let result = Tree::from_str("CA|CB|&").unwrap();
-        assert_eq!(distribute_to_right(node.root.clone()), result.root);
+        assert_eq!(distribute_to_right(node.root.clone(), And, Or), result.root);
         assert_eq!(TruthTable::from(node), TruthTable::from(result));
     }
+
+    #[test]
+    fn tseitin_matches_brute_force() {
+        let formulas = [
+            "AB|",
+            "AB&!",
+            "AA!&",
+            "AB^",
+            "AB=C|",
+            "AB>!C>",
+            "AB>BC>&AC!&&",
+            "ABC||D!&AB&!&",
+            "A1|B0|&",
+            "1",
+            "0",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let expected = (0..1u64 << count)
+                .filter(|&bitfield| tree.root.eval_with(&Assignment::from_bits(bitfield, count)))
+                .count() as u64;
+            let tseitin = encode(&tree.root, count, Encoding::Tseitin);
+            assert_eq!(
+                count_models(&tseitin, count),
+                expected.into(),
+                "{}",
+                formula
+            );
+            let plaisted_greenbaum = encode(&tree.root, count, Encoding::PlaistedGreenbaum);
+            let model = dpll::solve(&plaisted_greenbaum, count);
+            assert_eq!(model.is_some(), expected > 0, "{}", formula);
+            if let Some(model) = model {
+                assert!(tree.root.eval_with(&model), "{}", formula);
+            }
+        }
+    }
+
+    #[test]
+    fn tseitin_linear_size() {
+        // (x0 & y0) | (x1 & y1) | ... has 2^40 clauses once distributed
+        let formula: String = (0..40)
+            .map(|i| format!("[x{}][y{}]&", i, i))
+            .collect::<String>()
+            + &"|".repeat(39);
+        let tree = Tree::from_str(&formula).unwrap();
+        let tseitin = encode(&tree.root, 80, Encoding::Tseitin);
+        assert_eq!(tseitin.len(), 40 * 3 + 39 * 3 + 1);
+        assert!(tseitin.iter().flatten().all(|l| l.variable < 80 + 79));
+        let plaisted_greenbaum = encode(&tree.root, 80, Encoding::PlaistedGreenbaum);
+        assert_eq!(plaisted_greenbaum.len(), 40 * 2 + 39 + 1);
+        assert!(tree.sat());
+    }
+
+    #[test]
+    fn flat_cnf_canonical() {
+        let cnf = |formula: &str| Cnf::from(&Tree::from_str(formula).unwrap().root);
+        assert_eq!(cnf("AB|C|D|"), cnf("DCBA|||"));
+        assert_eq!(cnf("AB|C|D|"), cnf("AB|CD||"));
+        // duplicate literals, tautologies and subsumed clauses
+        assert_eq!(cnf("AA|B&"), cnf("BA&"));
+        assert_eq!(cnf("AA!|B&").clauses, vec![vec![Literal::positive(1)]]);
+        assert_eq!(cnf("AB|A&CA|&"), cnf("A"));
+        assert_eq!(
+            cnf("CB|A&").clauses,
+            vec![
+                vec![Literal::positive(0)],
+                vec![Literal::positive(1), Literal::positive(2)]
+            ]
+        );
+        assert_eq!(cnf("AA!|").clauses, Vec::<Vec<Literal>>::new());
+        assert_eq!(cnf("A0&").clauses, vec![vec![]]);
+    }
+
+    #[test]
+    fn flat_cnf_to_node() {
+        for formula in ["AB&C|", "AB|C|D|", "AB>BC>&", "AB^C&", "AA!|", "AA!&"] {
+            let tree = Tree::from_str(formula).unwrap();
+            let variables = tree.variable_list.clone().unwrap_or_default();
+            let node = Cnf::from(&tree.root).to_node(&variables);
+            let count = tree.variable_count();
+            for bitfield in 0..1u64 << count {
+                let assignment = Assignment::from_bits(bitfield, count);
+                assert_eq!(
+                    node.eval_with(&assignment),
+                    tree.root.eval_with(&assignment),
+                    "{}",
+                    formula
+                );
+            }
+            assert_eq!(Cnf::from(&node), Cnf::from(&tree.root));
+        }
+        let tree = Tree::from_str("DA!|CB|&").unwrap();
+        let variables = tree.variable_list.clone().unwrap();
+        assert_eq!(
+            Cnf::from(&tree.root).to_node(&variables).to_rpn(),
+            "A!D|BC|&"
+        );
+    }
 }
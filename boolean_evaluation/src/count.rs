@@ -0,0 +1,353 @@
+use crate::big_uint::BigUint;
+use crate::literal::{Clause, Literal};
+use std::collections::{BTreeSet, HashMap};
+
+/// Counts the assignments of `variable_count` variables satisfying every clause.
+///
+/// The search propagates unit clauses, splits the remaining clauses into components sharing no
+/// variable, counts each component on its own and multiplies the results, branching only inside
+/// a component. Counts of components already met are reused from a cache, and variables that
+/// drop out of every clause contribute a factor of two each.
+pub fn count_models(clauses: &[Clause], variable_count: usize) -> BigUint {
+    Counter::new(|_| BigUint::one()).run(clauses, variable_count)
+}
+
+/// Weights of the two literals of a variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiteralWeights {
+    pub positive: f64,
+    pub negative: f64,
+}
+
+impl LiteralWeights {
+    /// Variable true with probability `p`, independently of the others.
+    pub fn probability(p: f64) -> Self {
+        Self {
+            positive: p,
+            negative: 1.0 - p,
+        }
+    }
+}
+
+/// Sums, over the assignments satisfying every clause, the product of the weights of the
+/// literals they make true, `weights` holding one entry per variable. With
+/// [`LiteralWeights::probability`] this is the probability that the clauses hold.
+///
+/// Uses the same search as [`count_models`].
+pub fn weighted_count(clauses: &[Clause], weights: &[LiteralWeights]) -> f64 {
+    Counter::new(|l: Literal| {
+        let w = weights[l.variable];
+        if l.negated {
+            w.negative
+        } else {
+            w.positive
+        }
+    })
+    .run(clauses, weights.len())
+}
+
+/// What the counter adds and multiplies: plain counts or real weights.
+trait Weight: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+impl Weight for BigUint {
+    fn zero() -> Self {
+        BigUint::zero()
+    }
+
+    fn one() -> Self {
+        BigUint::one()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+impl Weight for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn one() -> Self {
+        1.0
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+// Sorts the literals, returns None for a clause that is always true.
+fn normalize(clause: &Clause) -> Option<Clause> {
+    let mut clause = clause.clone();
+    clause.sort_unstable();
+    clause.dedup();
+    if clause.windows(2).any(|w| w[0].variable == w[1].variable) {
+        None
+    } else {
+        Some(clause)
+    }
+}
+
+fn variables(clauses: &[Clause]) -> BTreeSet<usize> {
+    clauses.iter().flatten().map(|l| l.variable).collect()
+}
+
+// Assigns unit clauses until none is left, returning the simplified clauses and the literals
+// made true, or None on a conflict.
+fn propagate(mut clauses: Vec<Clause>) -> Option<(Vec<Clause>, Vec<Literal>)> {
+    let mut assigned = vec![];
+    while let Some(clause) = clauses.iter().find(|c| c.len() <= 1) {
+        let &unit = clause.first()?;
+        assigned.push(unit);
+        clauses = clauses
+            .into_iter()
+            .filter(|c| !c.contains(&unit))
+            .map(|c| c.into_iter().filter(|&l| l != !unit).collect())
+            .collect();
+    }
+    Some((clauses, assigned))
+}
+
+// Groups the clauses linked by shared variables, with a union-find over the variables.
+fn components(clauses: Vec<Clause>) -> Vec<Vec<Clause>> {
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+    fn find(parent: &mut HashMap<usize, usize>, v: usize) -> usize {
+        let p = *parent.entry(v).or_insert(v);
+        if p == v {
+            return v;
+        }
+        let root = find(parent, p);
+        parent.insert(v, root);
+        root
+    }
+    for clause in &clauses {
+        let first = find(&mut parent, clause[0].variable);
+        for literal in &clause[1..] {
+            let other = find(&mut parent, literal.variable);
+            parent.insert(other, first);
+        }
+    }
+    let mut groups: HashMap<usize, Vec<Clause>> = HashMap::new();
+    for clause in clauses {
+        let root = find(&mut parent, clause[0].variable);
+        groups.entry(root).or_default().push(clause);
+    }
+    groups.into_values().collect()
+}
+
+struct Counter<W: Weight, F: Fn(Literal) -> W> {
+    cache: HashMap<Vec<Clause>, W>,
+    weight: F,
+}
+
+impl<W: Weight, F: Fn(Literal) -> W> Counter<W, F> {
+    fn new(weight: F) -> Self {
+        Self {
+            cache: HashMap::new(),
+            weight,
+        }
+    }
+
+    fn run(mut self, clauses: &[Clause], variable_count: usize) -> W {
+        let clauses: Vec<Clause> = clauses.iter().filter_map(normalize).collect();
+        let used = variables(&clauses);
+        let variable_count = used
+            .last()
+            .map_or(variable_count, |v| variable_count.max(v + 1));
+        let unused = (0..variable_count).filter(|v| !used.contains(v));
+        self.free(unused).mul(&self.count(clauses))
+    }
+
+    // Weight of variables constrained by nothing, which take both values.
+    fn free(&self, variables: impl Iterator<Item = usize>) -> W {
+        variables.fold(W::one(), |product, v| {
+            let both =
+                (self.weight)(Literal::positive(v)).add(&(self.weight)(Literal::negative(v)));
+            product.mul(&both)
+        })
+    }
+
+    // Counts over exactly the variables occurring in `clauses`.
+    fn count(&mut self, clauses: Vec<Clause>) -> W {
+        let before = variables(&clauses);
+        let Some((clauses, assigned)) = propagate(clauses) else {
+            return W::zero();
+        };
+        let after = variables(&clauses);
+        let free = before
+            .into_iter()
+            .filter(|v| !after.contains(v) && !assigned.iter().any(|l| l.variable == *v));
+        let fixed = assigned.iter().fold(self.free(free), |product, &l| {
+            product.mul(&(self.weight)(l))
+        });
+        components(clauses)
+            .into_iter()
+            .fold(fixed, |product, component| {
+                product.mul(&self.count_component(component))
+            })
+    }
+
+    fn count_component(&mut self, mut component: Vec<Clause>) -> W {
+        component.sort_unstable();
+        if let Some(count) = self.cache.get(&component) {
+            return count.clone();
+        }
+        let variable = branching_variable(&component);
+        let mut when_true = component.clone();
+        when_true.push(vec![Literal::positive(variable)]);
+        let mut when_false = component.clone();
+        when_false.push(vec![Literal::negative(variable)]);
+        let count = self.count(when_true).add(&self.count(when_false));
+        self.cache.insert(component, count.clone());
+        count
+    }
+}
+
+// The variable occurring most often, preferring short clauses.
+fn branching_variable(component: &[Clause]) -> usize {
+    let mut scores: HashMap<usize, f64> = HashMap::new();
+    for clause in component {
+        let weight = 1.0 / clause.len() as f64;
+        for literal in clause {
+            *scores.entry(literal.variable).or_insert(0.0) += weight;
+        }
+    }
+    scores
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(v, _)| v)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod count_test {
+    use crate::assignment::Assignment;
+    use crate::big_uint::BigUint;
+    use crate::count::LiteralWeights;
+    use crate::tree::Tree;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_count_matches_brute_force() {
+        let formulas = [
+            "AB|",
+            "AB&",
+            "AA!&",
+            "AB^",
+            "AB=C|",
+            "AB>BC>&",
+            "AB|C&D|",
+            "ABC||D!&AB&!&",
+            "AB&CD&|EF&|",
+            "1",
+            "0",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let expected = (0..1u64 << count)
+                .filter(|&bitfield| tree.root.eval_with(&Assignment::from_bits(bitfield, count)))
+                .count();
+            assert_eq!(
+                tree.count_models(),
+                BigUint::from(expected as u64),
+                "{}",
+                formula
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_many_variables() {
+        // 60 independent `x | y` pairs over 120 variables: 3^60 models
+        let formula: String = (0..60)
+            .map(|i| format!("[x{}][y{}]|", i, i))
+            .collect::<Vec<_>>()
+            .join("")
+            + &"&".repeat(59);
+        let tree = Tree::from_str(&formula).unwrap();
+        assert_eq!(
+            tree.count_models().to_string(),
+            "42391158275216203514294433201"
+        );
+
+        // an implication chain over 150 variables: x0 -> x1 -> ... -> x149 has 151 models
+        let names: Vec<String> = (0..150).map(|i| format!("[x{}]", i)).collect();
+        let mut formula = String::new();
+        for (i, pair) in names.windows(2).enumerate() {
+            formula.push_str(&format!("{}{}>", pair[0], pair[1]));
+            if i > 0 {
+                formula.push('&');
+            }
+        }
+        let tree = Tree::from_str(&formula).unwrap();
+        assert_eq!(tree.count_models(), BigUint::from(151));
+    }
+
+    #[test]
+    fn test_weighted_count_matches_brute_force() {
+        let formulas = ["AB|", "AB^", "AB>BC>&", "AB=C|", "ABC||D!&AB&!&", "1", "0"];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let weights: Vec<LiteralWeights> = (0..count)
+                .map(|i| LiteralWeights {
+                    positive: 0.5 + i as f64,
+                    negative: 2.0 - 0.25 * i as f64,
+                })
+                .collect();
+            let expected: f64 = (0..1u64 << count)
+                .map(|bitfield| Assignment::from_bits(bitfield, count))
+                .filter(|assignment| tree.root.eval_with(assignment))
+                .map(|assignment| {
+                    weights
+                        .iter()
+                        .enumerate()
+                        .map(|(i, w)| {
+                            if assignment.get(i) {
+                                w.positive
+                            } else {
+                                w.negative
+                            }
+                        })
+                        .product::<f64>()
+                })
+                .sum();
+            let weighted = tree.weighted_count(&weights);
+            assert!((weighted - expected).abs() < 1e-9, "{}", formula);
+        }
+    }
+
+    #[test]
+    fn test_probability() {
+        let (a, b) = (0.9, 0.8);
+        let and = Tree::from_str("AB&").unwrap().probability(&[a, b]);
+        assert!((and - a * b).abs() < 1e-12);
+        let or = Tree::from_str("AB|").unwrap().probability(&[a, b]);
+        assert!((or - (1.0 - (1.0 - a) * (1.0 - b))).abs() < 1e-12);
+        let xor = Tree::from_str("AB^").unwrap().probability(&[a, b]);
+        assert!((xor - (a * (1.0 - b) + (1.0 - a) * b)).abs() < 1e-12);
+        assert_eq!(Tree::from_str("AA!&").unwrap().probability(&[a]), 0.0);
+
+        // 100 components in series, each working with probability 0.99
+        let formula: String =
+            (0..100).map(|i| format!("[c{}]", i)).collect::<String>() + &"&".repeat(99);
+        let tree = Tree::from_str(&formula).unwrap();
+        let series = tree.probability(&[0.99; 100]);
+        assert!((series - 0.99f64.powi(100)).abs() < 1e-12);
+    }
+}
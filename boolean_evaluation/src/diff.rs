@@ -0,0 +1,211 @@
+//! Where two formulas disagree.
+//!
+//! The assignments on which two formulas differ are those satisfying their XOR miter. A
+//! [`Diff`] gives them as a small cover of cubes found by [`espresso::minimize`], split by which
+//! formula is true, and prints the cover in the style of a
+//! [`TruthTable`](crate::truth_table::TruthTable), `-` marking a variable left free by a cube.
+
+use crate::allsat::Models;
+use crate::big_uint::BigUint;
+use crate::dnf::{dnf_cubes, Dnf};
+use crate::espresso;
+use crate::literal::Cube;
+use crate::node::Node;
+use crate::tree::Tree;
+use crate::truth_table::format_cells;
+use std::fmt::{Display, Formatter};
+use Node::*;
+
+/// Assignments on which two formulas take different values, over the variables of both
+/// matched by name.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// True exactly where the formulas differ.
+    miter: Tree,
+    /// Cubes of the cover, each with the value the first formula takes on it.
+    cubes: Vec<(Cube, bool)>,
+}
+
+impl Diff {
+    /// Compares `lhs` with `rhs`.
+    pub fn new(lhs: &Tree, rhs: &Tree) -> Self {
+        let (variables, lhs, rhs) = lhs.merge(rhs);
+        let on_sets = [
+            (true, lhs.clone() & !rhs.clone()),
+            (false, !lhs.clone() & rhs.clone()),
+        ];
+        let mut cubes = vec![];
+        for (value, on) in on_sets {
+            let cover = espresso::minimize(&on, &Constant(false), &variables);
+            let dnf = Dnf::new(dnf_cubes(&cover));
+            cubes.extend(dnf.cubes.into_iter().map(|cube| (cube, value)));
+        }
+        let miter = Tree::from_variables(lhs ^ rhs, variables);
+        Self { miter, cubes }
+    }
+
+    /// Whether the formulas are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.cubes.is_empty()
+    }
+
+    /// Names of the variables of both formulas, literal `i` of a cube being `variables()[i]`.
+    pub fn variables(&self) -> Vec<String> {
+        self.miter.variable_names()
+    }
+
+    /// Cover of the disagreeing assignments, first the cubes where the first formula is true
+    /// and the second false, then the others. Cubes of the same side do not contain each other
+    /// but may overlap.
+    pub fn cubes(&self) -> &[(Cube, bool)] {
+        &self.cubes
+    }
+
+    /// Lazily enumerates the disagreeing assignments one by one.
+    pub fn assignments(&self) -> Models {
+        self.miter.models()
+    }
+
+    /// Number of disagreeing assignments.
+    pub fn count(&self) -> BigUint {
+        self.miter.count_models()
+    }
+}
+
+impl Display for Diff {
+    /// One row per cube, with the values of both formulas in the last two columns, headed `=lhs`
+    /// and `=rhs` so that they cannot be taken for variables.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let variables = self.variables();
+        let mut columns: Vec<&str> = variables.iter().map(String::as_str).collect();
+        columns.extend(["=lhs", "=rhs"]);
+        let bit = |value: bool| if value { '1' } else { '0' };
+        let rows: Vec<Vec<char>> = self
+            .cubes
+            .iter()
+            .map(|(cube, value)| {
+                let mut row = vec!['-'; variables.len()];
+                for literal in cube {
+                    row[literal.variable] = bit(!literal.negated);
+                }
+                row.extend([bit(*value), bit(!value)]);
+                row
+            })
+            .collect();
+        write!(f, "{}", format_cells(&columns, &rows))
+    }
+}
+
+#[cfg(test)]
+mod diff_test {
+    use crate::assignment::Assignment;
+    use crate::big_uint::BigUint;
+    use crate::literal::Literal;
+    use crate::tree::Tree;
+    use std::str::FromStr;
+
+    fn tree(formula: &str) -> Tree {
+        Tree::from_str(formula).unwrap()
+    }
+
+    #[test]
+    fn test_diff_cubes() {
+        // `A | B` and `C` swapped places with `A & B` and `C`: both differences make the second
+        // formula true
+        let diff = tree("AB|C&").diff(&tree("AB&C|"));
+        assert!(!diff.is_empty());
+        assert_eq!(diff.variables(), vec!["A", "B", "C"]);
+        assert_eq!(
+            diff.cubes(),
+            &[
+                (
+                    vec![
+                        Literal::positive(0),
+                        Literal::positive(1),
+                        Literal::negative(2)
+                    ],
+                    false
+                ),
+                (
+                    vec![
+                        Literal::negative(0),
+                        Literal::negative(1),
+                        Literal::positive(2)
+                    ],
+                    false
+                ),
+            ]
+        );
+        assert_eq!(diff.count(), BigUint::from(2));
+        assert_eq!(diff.assignments().count(), 2);
+    }
+
+    #[test]
+    fn test_diff_matches_brute_force() {
+        // pairs over the same variables, so that one assignment serves both
+        let pairs = [
+            ("AB>C=", "AB&C|"),
+            ("AB^BC^&", "AC=B&"),
+            ("ABC||D!&", "AB|CD&|D!&"),
+            ("AB&!", "A!B!|"),
+        ];
+        for (lhs, rhs) in pairs {
+            let (lhs, rhs) = (tree(lhs), tree(rhs));
+            let diff = lhs.diff(&rhs);
+            let count = lhs.variable_count();
+            let mut differing = 0u64;
+            for bitfield in 0..1u64 << count {
+                let assignment = Assignment::from_bits(bitfield, count);
+                let (a, b) = (
+                    lhs.root.eval_with(&assignment),
+                    rhs.root.eval_with(&assignment),
+                );
+                let covering = diff
+                    .cubes()
+                    .iter()
+                    .find(|(cube, _)| cube.iter().all(|l| assignment.get(l.variable) != l.negated));
+                assert_eq!(covering.is_some(), a != b);
+                if let Some((_, value)) = covering {
+                    assert_eq!(*value, a);
+                }
+                differing += (a != b) as u64;
+            }
+            assert_eq!(diff.count(), BigUint::from(differing));
+            assert_eq!(diff.is_empty(), differing == 0);
+        }
+    }
+
+    #[test]
+    fn test_diff_display() {
+        let diff = tree("[x]Y&").diff(&tree("[x]"));
+        assert_eq!(
+            diff.to_string(),
+            "| Y | x | =lhs | =rhs |\n\
+             |---|---|------|------|\n\
+             | 0 | 1 | 0    | 1    |\n"
+        );
+        // variables of one formula only are compared too, `-` where they do not matter
+        let diff = tree("AB|").diff(&tree("C"));
+        assert_eq!(
+            diff.to_string(),
+            "| A | B | C | =lhs | =rhs |\n\
+             |---|---|---|------|------|\n\
+             | 1 | - | 0 | 1    | 0    |\n\
+             | - | 1 | 0 | 1    | 0    |\n\
+             | 0 | 0 | 1 | 0    | 1    |\n"
+        );
+        assert_eq!(
+            tree("AB&!").diff(&tree("A!B!|")).to_string(),
+            "| A | B | =lhs | =rhs |\n\
+             |---|---|------|------|\n"
+        );
+        // variables named like the formulas do not get mixed up with them
+        assert_eq!(
+            tree("[lhs]").diff(&tree("[rhs]")).to_string(),
+            "| lhs | rhs | =lhs | =rhs |\n\
+             |-----|-----|------|------|\n\
+             | 1   | 0   | 1    | 0    |\n\
+             | 0   | 1   | 0    | 1    |\n"
+        );
+    }
+}
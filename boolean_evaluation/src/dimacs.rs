@@ -0,0 +1,319 @@
+//! Reading and writing clauses in the DIMACS `p cnf` format used by SAT solvers.
+//!
+//! Variable `i` of a clause set is DIMACS variable `i + 1`. Names travel in comments of the form
+//! `c var <variable> <name>`, so formulas read back with the letters they were written with;
+//! any other comment is left alone.
+
+use crate::cnf::Cnf;
+use crate::error::DimacsError;
+use crate::error::DimacsError::*;
+use crate::literal::{Clause, Literal};
+use crate::tree::Tree;
+use crate::variable::Variable;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+/// Clauses read from DIMACS text, with the names the comments give to the variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dimacs {
+    /// Number of variables declared by the header.
+    pub variable_count: usize,
+    /// Names given by the comments, by variable index.
+    pub given_names: BTreeMap<usize, String>,
+    pub clauses: Vec<Clause>,
+}
+
+impl Dimacs {
+    /// A name for each declared variable: the given one, otherwise `x<variable>` followed by as
+    /// many `_` as it takes not to be a given name.
+    pub fn names(&self) -> Vec<String> {
+        let taken: HashSet<&str> = self.given_names.values().map(String::as_str).collect();
+        (0..self.variable_count)
+            .map(|i| match self.given_names.get(&i) {
+                Some(name) => name.clone(),
+                None => {
+                    let mut name = format!("x{}", i + 1);
+                    while taken.contains(name.as_str()) {
+                        name.push('_');
+                    }
+                    name
+                }
+            })
+            .collect()
+    }
+
+    /// Conjunction of the clauses over variables named after [`Dimacs::names`].
+    pub fn to_tree(&self) -> Tree {
+        Tree::from_names(&self.names(), |variables| {
+            Cnf::new(self.clauses.clone()).to_node(variables)
+        })
+    }
+}
+
+/// Writes `clauses` with a header and a name comment for each of `names`.
+///
+/// Variables past the names, such as the auxiliary variables of a Tseitin encoding, are counted
+/// in the header but left unnamed.
+pub fn write(clauses: &[Clause], names: &[String]) -> String {
+    let variable_count = clauses
+        .iter()
+        .flatten()
+        .map(|l| l.variable + 1)
+        .fold(names.len(), usize::max);
+    let mut s = String::new();
+    for (i, name) in names.iter().enumerate() {
+        writeln!(s, "c var {} {}", i + 1, name).unwrap();
+    }
+    writeln!(s, "p cnf {} {}", variable_count, clauses.len()).unwrap();
+    for clause in clauses {
+        for literal in clause {
+            let sign = if literal.negated { "-" } else { "" };
+            write!(s, "{}{} ", sign, literal.variable + 1).unwrap();
+        }
+        s.push_str("0\n");
+    }
+    s
+}
+
+/// Reads DIMACS text. Comments `c var <variable> <name>` naming a declared variable with a valid
+/// name give it that name, other comments are ignored, and a `%` line ends the input as in the
+/// SATLIB benchmarks.
+pub fn parse(s: &str) -> Result<Dimacs, DimacsError> {
+    // (variable, name, line) of the name comments
+    let mut comments: Vec<(usize, &str, usize)> = vec![];
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses = vec![];
+    let mut clause: Clause = vec![];
+
+    for (line, text) in s.lines().enumerate() {
+        let mut words = text.split_whitespace().peekable();
+        match words.peek() {
+            None => continue,
+            Some(&"%") => break,
+            Some(&"c") => {
+                words.next();
+                if let (Some("var"), Some(Ok(variable)), Some(name), None) = (
+                    words.next(),
+                    words.next().map(str::parse::<usize>),
+                    words.next(),
+                    words.next(),
+                ) {
+                    if Variable::is_valid_name(name) {
+                        comments.push((variable, name, line));
+                    }
+                }
+            }
+            Some(&"p") => {
+                let declared = match words.collect::<Vec<_>>()[..] {
+                    ["p", "cnf", variables, count] => {
+                        variables.parse::<usize>().ok().zip(count.parse().ok())
+                    }
+                    _ => None,
+                };
+                if header.is_some() {
+                    return Err(InvalidHeader { line });
+                }
+                header = Some(declared.ok_or(InvalidHeader { line })?);
+            }
+            Some(_) => {
+                let (variable_count, _) = header.ok_or(MissingHeader)?;
+                for word in words {
+                    let value: i64 = word.parse().map_err(|_| InvalidLiteral {
+                        line,
+                        token: word.to_string(),
+                    })?;
+                    let variable = value.unsigned_abs() as usize;
+                    if value == 0 {
+                        clauses.push(std::mem::take(&mut clause));
+                    } else if variable > variable_count {
+                        return Err(VariableOutOfRange { line, variable });
+                    } else if value > 0 {
+                        clause.push(Literal::positive(variable - 1));
+                    } else {
+                        clause.push(Literal::negative(variable - 1));
+                    }
+                }
+            }
+        }
+    }
+
+    let (variable_count, expected) = header.ok_or(MissingHeader)?;
+    if !clause.is_empty() {
+        return Err(UnterminatedClause);
+    }
+    if clauses.len() != expected {
+        return Err(ClauseCountMismatch {
+            expected,
+            found: clauses.len(),
+        });
+    }
+    Ok(Dimacs {
+        variable_count,
+        given_names: given_names(variable_count, &comments)?,
+        clauses,
+    })
+}
+
+// Comments about variables the header does not declare are plain comments. Of two comments
+// about the same variable the last one wins.
+fn given_names(
+    variable_count: usize,
+    comments: &[(usize, &str, usize)],
+) -> Result<BTreeMap<usize, String>, DimacsError> {
+    let mut given: BTreeMap<usize, (&str, usize)> = BTreeMap::new();
+    for &(variable, name, line) in comments {
+        if (1..=variable_count).contains(&variable) {
+            given.insert(variable - 1, (name, line));
+        }
+    }
+    let mut lines: HashMap<&str, usize> = HashMap::new();
+    for &(name, line) in given.values() {
+        if let Some(other) = lines.insert(name, line) {
+            // blame the later of the two comments
+            return Err(DuplicateName {
+                line: line.max(other),
+                name: name.to_string(),
+            });
+        }
+    }
+    Ok(given
+        .into_iter()
+        .map(|(variable, (name, _))| (variable, name.to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod dimacs_test {
+    use crate::assignment::Assignment;
+    use crate::cnf::Cnf;
+    use crate::dimacs::{parse, write};
+    use crate::error::DimacsError::*;
+    use crate::literal::Literal;
+    use crate::tree::Tree;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_write() {
+        let tree = Tree::from_str("AB&C|").unwrap();
+        assert_eq!(
+            tree.to_dimacs(),
+            "c var 1 A\nc var 2 B\nc var 3 C\np cnf 3 2\n1 3 0\n2 3 0\n"
+        );
+        assert_eq!(write(&[vec![]], &[]), "p cnf 0 1\n0\n");
+        assert_eq!(
+            write(&[vec![Literal::negative(2)]], &["A".to_string()]),
+            "c var 1 A\np cnf 3 1\n-3 0\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for formula in ["AB&C|", "AB^C!&", "[left][right]>[up]=", "A0&", "A1|"] {
+            let tree = Tree::from_str(formula).unwrap();
+            let dimacs = parse(&tree.to_dimacs()).unwrap();
+            assert_eq!(dimacs.names(), tree.variable_names());
+            assert_eq!(
+                Cnf::new(dimacs.clauses.clone()),
+                Cnf::from(&tree.root),
+                "{}",
+                formula
+            );
+            let read = dimacs.to_tree();
+            assert_eq!(read.variable_names(), tree.variable_names());
+            let count = tree.variable_count();
+            for bitfield in 0..1u64 << count {
+                let assignment = Assignment::from_bits(bitfield, count);
+                assert_eq!(
+                    read.root.eval_with(&assignment),
+                    tree.root.eval_with(&assignment),
+                    "{}",
+                    formula
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "c an example\nc var 2 rain\nc 3 clauses\np cnf 3 2\n 1 -2\n  0 2 3 0\n%\n0\n";
+        let dimacs = parse(text).unwrap();
+        assert_eq!(dimacs.variable_count, 3);
+        assert_eq!(
+            dimacs.given_names,
+            BTreeMap::from([(1, "rain".to_string())])
+        );
+        assert_eq!(dimacs.names(), vec!["x1", "rain", "x3"]);
+        assert_eq!(
+            dimacs.clauses,
+            vec![
+                vec![Literal::positive(0), Literal::negative(1)],
+                vec![Literal::positive(1), Literal::positive(2)],
+            ]
+        );
+        // DIMACS variable `i` stays variable `i - 1` whatever its name
+        let tree = dimacs.to_tree();
+        assert_eq!(tree.variable_names(), vec!["x1", "rain", "x3"]);
+        assert_eq!(tree.root.to_rpn(), "[x1][rain]!|[rain][x3]|&");
+        assert_eq!(parse(&tree.to_dimacs()).unwrap().names(), dimacs.names());
+
+        // the header alone allocates nothing per variable
+        let dimacs = parse("p cnf 18446744073709551615 0\n").unwrap();
+        assert_eq!(dimacs.variable_count, usize::MAX);
+        assert!(dimacs.given_names.is_empty());
+    }
+
+    #[test]
+    fn test_default_names() {
+        // a given name equal to the default name of another variable pushes that one aside
+        let dimacs = parse("c var 1 x2\nc var 3 x2_\np cnf 3 0\n").unwrap();
+        assert_eq!(dimacs.names(), vec!["x2", "x2__", "x2_"]);
+        assert_eq!(dimacs.to_tree().variable_count(), 3);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("1 2 0\n").unwrap_err(), MissingHeader);
+        assert_eq!(parse("c nothing\n").unwrap_err(), MissingHeader);
+        assert_eq!(parse("p cnf x 1\n").unwrap_err(), InvalidHeader { line: 0 });
+        assert_eq!(
+            parse("p cnf 2 1\n1 b 0\n").unwrap_err(),
+            InvalidLiteral {
+                line: 1,
+                token: "b".to_string()
+            }
+        );
+        assert_eq!(
+            parse("p cnf 2 1\n\n1 -3 0\n").unwrap_err(),
+            VariableOutOfRange {
+                line: 2,
+                variable: 3
+            }
+        );
+        assert_eq!(parse("p cnf 2 1\n1 2\n").unwrap_err(), UnterminatedClause);
+        assert_eq!(
+            parse("p cnf 2 1\n1 0 2 0\n").unwrap_err(),
+            ClauseCountMismatch {
+                expected: 1,
+                found: 2
+            }
+        );
+        assert_eq!(
+            parse("c var 1 A\nc var 2 A\np cnf 2 0\n").unwrap_err(),
+            DuplicateName {
+                line: 1,
+                name: "A".to_string()
+            }
+        );
+        assert_eq!(
+            parse("c var 1 B\nc var 1 A\nc var 2 B\np cnf 2 0\n")
+                .unwrap()
+                .names(),
+            vec!["A", "B"]
+        );
+        assert_eq!(
+            parse("p cnf 2 1\n1 -3 0\n").unwrap_err().to_string(),
+            "variable 3 on line 2 is not declared by the header"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::literal::{canonical_terms, terms_to_node, Cube};
+use crate::node::Node;
+use crate::normal_form::{collect_terms, normal_form};
+use crate::operator::Operator;
+use crate::tree::VariableRef;
+use Operator::*;
+
+/// Disjunctive normal form: a disjunction of conjunctions of literals, equivalent to `node`.
+pub fn node_to_dnf(node: Node) -> Node {
+    normal_form(node, Or, And)
+}
+
+/// Collects the cubes of a formula in disjunctive normal form, such as the output of
+/// [`node_to_dnf`]. Cubes made false by a `0` are dropped and `1` literals are left out.
+pub fn dnf_cubes(node: &Node) -> Vec<Cube> {
+    collect_terms(node, Or, And)
+}
+
+/// Formula in disjunctive normal form as a flat set of cubes, kept canonical: literals sorted
+/// and distinct within each cube, no always false cube, no cube containing another, and cubes
+/// sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Dnf {
+    pub cubes: Vec<Cube>,
+}
+
+impl Dnf {
+    pub fn new(cubes: Vec<Cube>) -> Self {
+        Self {
+            cubes: canonical_terms(cubes),
+        }
+    }
+
+    /// Disjunction of right-nested conjunctions, `0` without cubes and `1` for an empty cube.
+    /// Literal `i` is variable `variables[i]`.
+    pub fn to_node(&self, variables: &[VariableRef]) -> Node {
+        terms_to_node(&self.cubes, variables, And, Or)
+    }
+}
+
+impl From<&Node> for Dnf {
+    /// Cubes of [`node_to_dnf`], equivalent to `node`.
+    fn from(node: &Node) -> Self {
+        Self::new(dnf_cubes(&node_to_dnf(node.clone())))
+    }
+}
+
+#[cfg(test)]
+mod dnf_test {
+    use crate::assignment::Assignment;
+    use crate::dnf::{dnf_cubes, node_to_dnf, Dnf};
+    use crate::literal::Literal;
+    use crate::normal_form::{distribute_both, distribute_to_left, distribute_to_right};
+    use crate::operator::Operator::*;
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    #[test]
+    fn basic_node_to_dnf() {
+        let node = Tree::from_str("ABC&DE||&").unwrap();
+        let result = Tree::from_str("ABC&&AD&AE&||").unwrap();
+        assert_eq!(node_to_dnf(node.root.clone()), result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
+    #[test]
+    fn basic_distribute_both() {
+        let node = Tree::from_str("AB|CD|&").unwrap();
+        let result = Tree::from_str("AC&AD&|BC&BD&||").unwrap();
+        assert_eq!(distribute_both(node.root.clone(), Or, And), result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
+    #[test]
+    fn basic_distribute_left() {
+        let node = Tree::from_str("AB|C&").unwrap();
+        let result = Tree::from_str("AC&BC&|").unwrap();
+        assert_eq!(distribute_to_left(node.root.clone(), Or, And), result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
+    #[test]
+    fn basic_distribute_right() {
+        let node = Tree::from_str("CAB|&").unwrap();
+        let result = Tree::from_str("CA&CB&|").unwrap();
+        assert_eq!(distribute_to_right(node.root.clone(), Or, And), result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
+    #[test]
+    fn nested_distribute_both() {
+        let node = Tree::from_str("AB|CD|&EF|&").unwrap();
+        let result = node_to_dnf(node.root.clone());
+        assert_eq!(dnf_cubes(&result).len(), 8);
+        assert!(dnf_cubes(&result).iter().all(|c| c.len() == 3));
+    }
+
+    #[test]
+    fn dnf_matches_truth_table() {
+        let formulas = [
+            "AB>!",
+            "AB=C^",
+            "AB>BC>&AC!&|",
+            "ABC||D!&AB&!&",
+            "AB^BC^&CA^&",
+            "A1&B0&|",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let dnf = node_to_dnf(tree.root.clone());
+            let variables = tree.variable_list.clone().unwrap_or_default();
+            let flat = Dnf::from(&tree.root).to_node(&variables);
+            let count = tree.variable_count();
+            for bitfield in 0..1u64 << count {
+                let assignment = Assignment::from_bits(bitfield, count);
+                let expected = tree.root.eval_with(&assignment);
+                assert_eq!(dnf.eval_with(&assignment), expected, "{}", formula);
+                assert_eq!(flat.eval_with(&assignment), expected, "{}", formula);
+            }
+        }
+    }
+
+    #[test]
+    fn constant_cubes() {
+        let node = Tree::from_str("A0&B1&|").unwrap();
+        assert_eq!(
+            dnf_cubes(&node_to_dnf(node.root)),
+            vec![vec![Literal::positive(1)]]
+        );
+        let node = Tree::from_str("1").unwrap();
+        assert_eq!(dnf_cubes(&node_to_dnf(node.root)), vec![vec![]]);
+    }
+
+    #[test]
+    fn flat_dnf_canonical() {
+        let dnf = |formula: &str| Dnf::from(&Tree::from_str(formula).unwrap().root);
+        assert_eq!(dnf("AB&C&D&"), dnf("DCBA&&&"));
+        assert_eq!(dnf("AA!&B|").cubes, vec![vec![Literal::positive(1)]]);
+        assert_eq!(dnf("AB&A|").cubes, vec![vec![Literal::positive(0)]]);
+        assert_eq!(dnf("AA!&").cubes, Vec::<Vec<Literal>>::new());
+        assert_eq!(dnf("A1|").cubes, vec![vec![]]);
+        let tree = Tree::from_str("DA!&CB&|").unwrap();
+        let variables = tree.variable_list.clone().unwrap();
+        assert_eq!(
+            Dnf::from(&tree.root).to_node(&variables).to_rpn(),
+            "A!D&BC&|"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::assignment::Assignment;
+use crate::literal::{Clause, Literal};
+
+/// Decides a set of clauses with the Davis–Putnam–Logemann–Loveland procedure: unit
+/// propagation, pure literal elimination, then branching on the literal occurring most often in
+/// the shortest open clauses.
+///
+/// Returns a satisfying assignment of the `variable_count` variables, variables left free by the
+/// search being `false`, or `None` when the clauses are unsatisfiable.
+pub fn solve(clauses: &[Clause], variable_count: usize) -> Option<Assignment> {
+    let variable_count = clauses
+        .iter()
+        .flatten()
+        .map(|l| l.variable + 1)
+        .fold(variable_count, usize::max);
+    let mut solver = Dpll {
+        clauses,
+        values: vec![None; variable_count],
+        trail: Vec::with_capacity(variable_count),
+    };
+    if solver.search() {
+        Some(solver.values.iter().map(|v| v.unwrap_or(false)).collect())
+    } else {
+        None
+    }
+}
+
+enum ClauseState {
+    Satisfied,
+    Falsified,
+    Unit(Literal),
+    Open,
+}
+
+struct Dpll<'a> {
+    clauses: &'a [Clause],
+    values: Vec<Option<bool>>,
+    // assigned variables in order, so backtracking can unassign them
+    trail: Vec<usize>,
+}
+
+impl Dpll<'_> {
+    fn search(&mut self) -> bool {
+        let mark = self.trail.len();
+        if !self.propagate() {
+            self.backtrack(mark);
+            return false;
+        }
+        self.eliminate_pure_literals();
+
+        match self.choose_literal() {
+            None => true,
+            Some(literal) => {
+                for literal in [literal, !literal] {
+                    let decision = self.trail.len();
+                    self.assign(literal);
+                    if self.search() {
+                        return true;
+                    }
+                    self.backtrack(decision);
+                }
+                self.backtrack(mark);
+                false
+            }
+        }
+    }
+
+    fn assign(&mut self, literal: Literal) {
+        self.values[literal.variable] = Some(!literal.negated);
+        self.trail.push(literal.variable);
+    }
+
+    fn backtrack(&mut self, mark: usize) {
+        for variable in self.trail.drain(mark..) {
+            self.values[variable] = None;
+        }
+    }
+
+    fn clause_state(&self, clause: &Clause) -> ClauseState {
+        let mut unassigned = None;
+        let mut open = 0;
+        for &literal in clause {
+            match self.values[literal.variable] {
+                Some(value) if literal.eval(value) => return ClauseState::Satisfied,
+                Some(_) => {}
+                None => {
+                    unassigned = Some(literal);
+                    open += 1;
+                }
+            }
+        }
+        match (open, unassigned) {
+            (0, _) => ClauseState::Falsified,
+            (1, Some(literal)) => ClauseState::Unit(literal),
+            _ => ClauseState::Open,
+        }
+    }
+
+    // Assigns unit clauses until none is left, returns false on a falsified clause.
+    fn propagate(&mut self) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for clause in self.clauses {
+                match self.clause_state(clause) {
+                    ClauseState::Falsified => return false,
+                    ClauseState::Unit(literal) => {
+                        self.assign(literal);
+                        changed = true;
+                    }
+                    ClauseState::Satisfied | ClauseState::Open => {}
+                }
+            }
+        }
+        true
+    }
+
+    // A variable appearing with a single polarity in the open clauses can take that polarity.
+    fn eliminate_pure_literals(&mut self) {
+        // bit 0: seen positive, bit 1: seen negative
+        let mut polarity = vec![0u8; self.values.len()];
+        for clause in self.clauses {
+            if let ClauseState::Satisfied = self.clause_state(clause) {
+                continue;
+            }
+            for literal in clause.iter().filter(|l| self.values[l.variable].is_none()) {
+                polarity[literal.variable] |= if literal.negated { 2 } else { 1 };
+            }
+        }
+        for (variable, seen) in polarity.into_iter().enumerate() {
+            match seen {
+                1 => self.assign(Literal::positive(variable)),
+                2 => self.assign(Literal::negative(variable)),
+                _ => {}
+            }
+        }
+    }
+
+    // Returns None once every clause is satisfied.
+    fn choose_literal(&self) -> Option<Literal> {
+        let mut shortest = usize::MAX;
+        let mut occurrences = vec![0usize; self.values.len() * 2];
+        for clause in self.clauses {
+            if let ClauseState::Satisfied = self.clause_state(clause) {
+                continue;
+            }
+            let open: Vec<&Literal> = clause
+                .iter()
+                .filter(|l| self.values[l.variable].is_none())
+                .collect();
+            if open.len() < shortest {
+                shortest = open.len();
+                occurrences.iter_mut().for_each(|n| *n = 0);
+            }
+            if open.len() == shortest {
+                for literal in open {
+                    occurrences[literal.variable * 2 + literal.negated as usize] += 1;
+                }
+            }
+        }
+        occurrences
+            .iter()
+            .enumerate()
+            .filter(|(_, &n)| n > 0)
+            .max_by_key(|(_, &n)| n)
+            .map(|(i, _)| Literal {
+                variable: i / 2,
+                negated: i % 2 == 1,
+            })
+    }
+}
+
+#[cfg(test)]
+mod dpll_test {
+    use crate::assignment::Assignment;
+    use crate::cnf::{cnf_clauses, node_to_cnf};
+    use crate::dpll::solve;
+    use crate::literal::Literal;
+    use crate::tree::Tree;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_dpll_matches_brute_force() {
+        let formulas = [
+            "AB|",
+            "AB&",
+            "AA!&",
+            "AA^",
+            "AB=A!B!&&",
+            "AB>BC>&AC!&&",
+            "AB^BC^&CA^&",
+            "AB|A!B|&AB!|&A!B!|&",
+            "ABC||D!&AB&!&",
+            "1",
+            "0",
+            "A0&",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let count = tree.variable_count();
+            let expected = (0..1u64 << count)
+                .any(|bitfield| tree.root.eval_with(&Assignment::from_bits(bitfield, count)));
+            let model = solve(&cnf_clauses(&node_to_cnf(tree.root.clone())), count);
+            assert_eq!(model.is_some(), expected, "{}", formula);
+            if let Some(model) = model {
+                assert!(tree.root.eval_with(&model), "{}", formula);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dpll_pigeonhole() {
+        // three pigeons, two holes: variable `2 * p + h` puts pigeon `p` in hole `h`
+        let mut clauses: Vec<Vec<Literal>> = (0..3)
+            .map(|p| vec![Literal::positive(2 * p), Literal::positive(2 * p + 1)])
+            .collect();
+        for h in 0..2 {
+            for p in 0..3 {
+                for q in p + 1..3 {
+                    clauses.push(vec![
+                        Literal::negative(2 * p + h),
+                        Literal::negative(2 * q + h),
+                    ]);
+                }
+            }
+        }
+        assert!(solve(&clauses, 6).is_none());
+        clauses.drain(0..1);
+        assert!(solve(&clauses, 6).is_some());
+    }
+
+    #[test]
+    fn test_dpll_many_variables() {
+        // x0 -> x1 -> ... -> x199 with x0 forced and x199 forbidden
+        let mut clauses: Vec<Vec<Literal>> = (0..199)
+            .map(|i| vec![Literal::negative(i), Literal::positive(i + 1)])
+            .collect();
+        clauses.push(vec![Literal::positive(0)]);
+        let model = solve(&clauses, 200).unwrap();
+        assert!(model.values().iter().all(|&v| v));
+        clauses.push(vec![Literal::negative(199)]);
+        assert!(solve(&clauses, 200).is_none());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::operator::Operator;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use ParseError::*;
+
+/// Everything that can go wrong while parsing a formula.
+///
+/// Positions are byte offsets into the input, [`Display`] reports them as 1-based columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    EmptyInput,
+    UnknownToken { position: usize, token: char },
+    MissingOperands { position: usize, operator: Operator },
+    LeftoverOperands { count: usize },
+    UnexpectedToken { position: usize },
+    UnclosedParenthesis { position: usize },
+    UnclosedBracket { position: usize },
+    InvalidName { position: usize },
+    UnexpectedEnd,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EmptyInput => write!(f, "empty input"),
+            UnknownToken { position, token } => {
+                write!(f, "unknown token `{}` at column {}", token, position + 1)
+            }
+            MissingOperands {
+                position,
+                operator: Operator::Not,
+            } => write!(
+                f,
+                "operator `!` at column {} needs an operand",
+                position + 1
+            ),
+            MissingOperands { position, operator } => write!(
+                f,
+                "operator `{:?}` at column {} needs two operands",
+                operator,
+                position + 1
+            ),
+            LeftoverOperands { count } => {
+                write!(f, "{} operands are not combined by any operator", count)
+            }
+            UnexpectedToken { position } => {
+                write!(f, "unexpected token at column {}", position + 1)
+            }
+            UnclosedParenthesis { position } => {
+                write!(f, "parenthesis at column {} is never closed", position + 1)
+            }
+            UnclosedBracket { position } => {
+                write!(f, "bracket at column {} is never closed", position + 1)
+            }
+            InvalidName { position } => {
+                write!(f, "invalid variable name at column {}", position + 1)
+            }
+            UnexpectedEnd => write!(f, "unexpected end of input"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Everything that can go wrong while reading DIMACS text.
+///
+/// Lines are counted from 0, [`Display`] reports them from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimacsError {
+    MissingHeader,
+    InvalidHeader { line: usize },
+    InvalidLiteral { line: usize, token: String },
+    VariableOutOfRange { line: usize, variable: usize },
+    DuplicateName { line: usize, name: String },
+    UnterminatedClause,
+    ClauseCountMismatch { expected: usize, found: usize },
+}
+
+impl Display for DimacsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::MissingHeader => write!(f, "missing `p cnf` header"),
+            DimacsError::InvalidHeader { line } => write!(f, "invalid header on line {}", line + 1),
+            DimacsError::InvalidLiteral { line, token } => {
+                write!(f, "invalid literal `{}` on line {}", token, line + 1)
+            }
+            DimacsError::VariableOutOfRange { line, variable } => write!(
+                f,
+                "variable {} on line {} is not declared by the header",
+                variable,
+                line + 1
+            ),
+            DimacsError::DuplicateName { line, name } => {
+                write!(f, "name `{}` on line {} is already taken", name, line + 1)
+            }
+            DimacsError::UnterminatedClause => write!(f, "last clause is not terminated by 0"),
+            DimacsError::ClauseCountMismatch { expected, found } => write!(
+                f,
+                "header declares {} clauses but {} were found",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Error for DimacsError {}
+
+#[cfg(test)]
+mod error_test {
+    use crate::error::ParseError;
+    use crate::operator::Operator;
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::MissingOperands {
+            position: 3,
+            operator: Operator::And,
+        };
+        assert_eq!(
+            error.to_string(),
+            "operator `&` at column 4 needs two operands"
+        );
+        let error = ParseError::MissingOperands {
+            position: 0,
+            operator: Operator::Not,
+        };
+        assert_eq!(
+            error.to_string(),
+            "operator `!` at column 1 needs an operand"
+        );
+        let error = ParseError::UnknownToken {
+            position: 2,
+            token: '@',
+        };
+        assert_eq!(error.to_string(), "unknown token `@` at column 3");
+    }
+}
@@ -0,0 +1,625 @@
+//! Heuristic two-level minimisation in the style of Espresso, for functions too wide for
+//! [`quine_mccluskey`](crate::quine_mccluskey).
+//!
+//! The function is kept as a cover: a list of cubes whose union is the set of true rows, never
+//! expanding the truth table. Starting from the cover of the formula, cubes are expanded as far
+//! as the false rows allow, redundant cubes are dropped, and cubes are reduced again to leave
+//! room for a different expansion, until a round brings no gain. The result is small but not
+//! guaranteed minimal.
+
+use crate::literal::{canonical_terms, terms_to_node, Cube, Literal};
+use crate::node::Node;
+use crate::operator::Operator::*;
+use crate::tree::VariableRef;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use Node::*;
+
+/// Set of variables, variable `i` being bit `i % 64` of word `i / 64`. Sets compared with one
+/// another always have the same number of words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn get(&self, variable: usize) -> bool {
+        self.0[variable / 64] & 1 << (variable % 64) != 0
+    }
+
+    fn and(&self, other: &Bits) -> Bits {
+        Bits(self.0.iter().zip(&other.0).map(|(a, b)| a & b).collect())
+    }
+
+    fn or(&self, other: &Bits) -> Bits {
+        Bits(self.0.iter().zip(&other.0).map(|(a, b)| a | b).collect())
+    }
+
+    fn xor(&self, other: &Bits) -> Bits {
+        Bits(self.0.iter().zip(&other.0).map(|(a, b)| a ^ b).collect())
+    }
+
+    fn and_not(&self, other: &Bits) -> Bits {
+        Bits(self.0.iter().zip(&other.0).map(|(a, b)| a & !b).collect())
+    }
+
+    fn variables(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.0.len() * 64).filter(|&v| self.get(v))
+    }
+}
+
+impl PartialOrd for Bits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bits {
+    // As numbers, the highest word first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+/// Rows a cube matches: variable `i` may be false when it is in `zero` and true when it is in
+/// `one`. A variable in neither makes the cube empty.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct BitCube {
+    zero: Bits,
+    one: Bits,
+}
+
+impl BitCube {
+    fn intersect(&self, other: &BitCube) -> BitCube {
+        BitCube {
+            zero: self.zero.and(&other.zero),
+            one: self.one.and(&other.one),
+        }
+    }
+
+    fn supercube(&self, other: &BitCube) -> BitCube {
+        BitCube {
+            zero: self.zero.or(&other.zero),
+            one: self.one.or(&other.one),
+        }
+    }
+
+    // Pairs of words of both halves, for the checks that should not allocate.
+    fn words(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.zero.0.iter().copied().zip(self.one.0.iter().copied())
+    }
+
+    fn contains(&self, other: &BitCube) -> bool {
+        self.words()
+            .zip(other.words())
+            .all(|((zero, one), (other_zero, other_one))| {
+                other_zero & !zero == 0 && other_one & !one == 0
+            })
+    }
+
+    // Variables taking a single value, for a cube that is not empty.
+    fn fixed(&self) -> Bits {
+        self.zero.xor(&self.one)
+    }
+
+    fn literal_count(&self) -> u32 {
+        self.words()
+            .map(|(zero, one)| (zero ^ one).count_ones())
+            .sum()
+    }
+}
+
+// Fewest literals first, ties broken by the cubes themselves.
+fn by_literal_count(a: &BitCube, b: &BitCube) -> Ordering {
+    a.literal_count().cmp(&b.literal_count()).then(a.cmp(b))
+}
+
+/// Cubes over the first `variable_count` variables.
+struct Space {
+    full: Bits,
+}
+
+impl Space {
+    fn new(variable_count: usize) -> Self {
+        let words = variable_count.div_ceil(64).max(1);
+        Self {
+            full: Bits(
+                (0..words)
+                    .map(|w| match variable_count.saturating_sub(w * 64) {
+                        0 => 0,
+                        n if n >= 64 => u64::MAX,
+                        n => (1 << n) - 1,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn bit(&self, variable: usize) -> Bits {
+        let mut bits = Bits(vec![0; self.full.0.len()]);
+        bits.0[variable / 64] = 1 << (variable % 64);
+        bits
+    }
+
+    fn universe(&self) -> BitCube {
+        BitCube {
+            zero: self.full.clone(),
+            one: self.full.clone(),
+        }
+    }
+
+    fn literal(&self, variable: usize, value: bool) -> BitCube {
+        let bit = self.bit(variable);
+        if value {
+            BitCube {
+                zero: self.full.and_not(&bit),
+                one: self.full.clone(),
+            }
+        } else {
+            BitCube {
+                zero: self.full.clone(),
+                one: self.full.and_not(&bit),
+            }
+        }
+    }
+
+    fn is_empty(&self, cube: &BitCube) -> bool {
+        cube.words()
+            .zip(&self.full.0)
+            .any(|((zero, one), &full)| (zero | one) & full != full)
+    }
+
+    fn is_universe(&self, cube: &BitCube) -> bool {
+        cube.words()
+            .zip(&self.full.0)
+            .all(|((zero, one), &full)| zero & one & full == full)
+    }
+
+    // Whether the intersection of the cubes is empty, without building it.
+    fn are_disjoint(&self, a: &BitCube, b: &BitCube) -> bool {
+        a.words().zip(b.words()).zip(&self.full.0).any(
+            |(((a_zero, a_one), (b_zero, b_one)), &full)| {
+                (a_zero & b_zero | a_one & b_one) & full != full
+            },
+        )
+    }
+
+    // Frees the variables `of` fixes, for cubes meeting it.
+    fn cofactor(&self, cover: &[BitCube], of: &BitCube) -> Vec<BitCube> {
+        let fixed = of.fixed().and(&self.full);
+        cover
+            .iter()
+            .filter(|c| !self.are_disjoint(c, of))
+            .map(|c| BitCube {
+                zero: c.zero.or(&fixed),
+                one: c.one.or(&fixed),
+            })
+            .collect()
+    }
+
+    // The variable fixed in the most cubes, preferring those fixed both ways.
+    fn splitting_variable(&self, cover: &[BitCube]) -> Option<(usize, bool)> {
+        self.full
+            .variables()
+            .filter_map(|v| {
+                let zeros = cover.iter().filter(|c| !c.one.get(v)).count();
+                let ones = cover.iter().filter(|c| !c.zero.get(v)).count();
+                (zeros + ones > 0).then_some((v, zeros > 0 && ones > 0, zeros + ones))
+            })
+            .max_by_key(|&(v, binate, count)| (binate, count, std::cmp::Reverse(v)))
+            .map(|(v, binate, _)| (v, binate))
+    }
+
+    // Whether the cover matches every row: a cover fixing each variable one way only does
+    // exactly when it holds the universe, otherwise both halves of a split must.
+    fn is_tautology(&self, cover: &[BitCube]) -> bool {
+        if cover.iter().any(|c| self.is_universe(c)) {
+            return true;
+        }
+        match self.splitting_variable(cover) {
+            Some((v, true)) => [false, true]
+                .iter()
+                .all(|&value| self.is_tautology(&self.cofactor(cover, &self.literal(v, value)))),
+            _ => false,
+        }
+    }
+
+    fn covers(&self, cover: &[BitCube], cube: &BitCube) -> bool {
+        self.is_tautology(&self.cofactor(cover, cube))
+    }
+
+    // Rows matched by no cube of the cover, splitting on one variable at a time.
+    fn complement(&self, cover: &[BitCube]) -> Vec<BitCube> {
+        if cover.is_empty() {
+            return vec![self.universe()];
+        }
+        if cover.iter().any(|c| self.is_universe(c)) {
+            return vec![];
+        }
+        if let [cube] = cover {
+            // De Morgan: one cube per literal, with the literal negated
+            let fixed = cube.fixed().and(&self.full);
+            return fixed
+                .variables()
+                .map(|v| self.literal(v, !cube.one.get(v)))
+                .collect();
+        }
+        let (v, _) = self.splitting_variable(cover).unwrap();
+        let when_false = self.complement(&self.cofactor(cover, &self.literal(v, false)));
+        let when_true = self.complement(&self.cofactor(cover, &self.literal(v, true)));
+        // a cube on both sides does not depend on the variable; each side is free of contained
+        // cubes already, so only the cubes kept whole can contain others
+        let true_side: HashSet<BitCube> = when_true.iter().cloned().collect();
+        let (both, when_false): (Vec<BitCube>, Vec<BitCube>) =
+            when_false.into_iter().partition(|c| true_side.contains(c));
+        let when_true = when_true
+            .into_iter()
+            .filter(|c| !both.iter().any(|b| b.contains(c)));
+        when_false
+            .into_iter()
+            .map(|c| c.intersect(&self.literal(v, false)))
+            .chain(when_true.map(|c| c.intersect(&self.literal(v, true))))
+            .chain(both.iter().cloned())
+            .collect()
+    }
+
+    // Smallest cube containing the complement of the cover, None when the complement is empty.
+    // Computed like the complement, without ever listing its cubes.
+    fn complement_supercube(&self, cover: &[BitCube]) -> Option<BitCube> {
+        if cover.is_empty() {
+            return Some(self.universe());
+        }
+        if cover.iter().any(|c| self.is_universe(c)) {
+            return None;
+        }
+        let (v, binate) = self.splitting_variable(cover).unwrap();
+        if !binate {
+            // without the universe a unate cover is no tautology, and only its single literal
+            // cubes fix variables in the complement, to the other value
+            let units = cover.iter().filter(|c| c.literal_count() == 1);
+            return Some(units.fold(self.universe(), |supercube, unit| {
+                supercube.intersect(&self.complement(std::slice::from_ref(unit))[0])
+            }));
+        }
+        [false, true]
+            .iter()
+            .filter_map(|&value| {
+                let literal = self.literal(v, value);
+                self.complement_supercube(&self.cofactor(cover, &literal))
+                    .map(|c| c.intersect(&literal))
+            })
+            .reduce(|a, b| a.supercube(&b))
+    }
+
+    fn cover_of(&self, node: &Node) -> Vec<BitCube> {
+        match node {
+            Variable(v) => vec![self.literal(v.index, true)],
+            Constant(true) => vec![self.universe()],
+            Constant(false) => vec![],
+            UnaryExpr { child, .. } => self.complement(&self.cover_of(child)),
+            BinaryExpr { op, lhs, rhs } => {
+                let a = self.cover_of(lhs);
+                let b = self.cover_of(rhs);
+                match op {
+                    And => self.product(&a, &b),
+                    Or => without_contained([a, b].concat()),
+                    Xor => {
+                        let only_a = self.product(&a, &self.complement(&b));
+                        let only_b = self.product(&self.complement(&a), &b);
+                        without_contained([only_a, only_b].concat())
+                    }
+                    Xnor => {
+                        let both = self.product(&a, &b);
+                        let neither = self.product(&self.complement(&a), &self.complement(&b));
+                        without_contained([both, neither].concat())
+                    }
+                    Imply => without_contained([self.complement(&a), b].concat()),
+                    Not => unreachable!(),
+                }
+            }
+        }
+    }
+
+    // Cubes of a wider space with the variables past this one freed: the rows where the cover
+    // matches for some value of them.
+    fn project(&self, cover: &[BitCube]) -> Vec<BitCube> {
+        let words = self.full.0.len();
+        let truncate = |bits: &Bits| Bits(bits.0[..words].to_vec()).and(&self.full);
+        let cubes = cover
+            .iter()
+            .map(|c| BitCube {
+                zero: truncate(&c.zero),
+                one: truncate(&c.one),
+            })
+            .collect();
+        without_contained(cubes)
+    }
+
+    fn product(&self, a: &[BitCube], b: &[BitCube]) -> Vec<BitCube> {
+        let cubes = a
+            .iter()
+            .flat_map(|x| b.iter().map(|y| x.intersect(y)))
+            .filter(|c| !self.is_empty(c))
+            .collect();
+        without_contained(cubes)
+    }
+
+    // Frees as many literals of each cube as the false rows allow, then drops the cubes the
+    // expanded ones contain. Larger cubes go first, they are the likeliest to absorb others.
+    fn expand(&self, mut on: Vec<BitCube>, off: &[BitCube]) -> Vec<BitCube> {
+        on.sort_unstable_by(by_literal_count);
+        let mut expanded: Vec<BitCube> = vec![];
+        for cube in on {
+            if expanded.iter().any(|e| e.contains(&cube)) {
+                continue;
+            }
+            let mut cube = cube;
+            // free first the literals other cubes disagree with most
+            let mut variables: Vec<usize> = cube.fixed().and(&self.full).variables().collect();
+            variables.sort_by_key(|&v| {
+                let raised = self.free(&cube, v);
+                std::cmp::Reverse(expanded.iter().filter(|e| raised.contains(e)).count())
+            });
+            for v in variables {
+                let raised = self.free(&cube, v);
+                if off.iter().all(|r| self.are_disjoint(&raised, r)) {
+                    cube = raised;
+                }
+            }
+            expanded.retain(|e| !cube.contains(e));
+            expanded.push(cube);
+        }
+        expanded
+    }
+
+    fn free(&self, cube: &BitCube, variable: usize) -> BitCube {
+        let bit = self.bit(variable);
+        BitCube {
+            zero: cube.zero.or(&bit),
+            one: cube.one.or(&bit),
+        }
+    }
+
+    // Drops cubes covered by the others and the don't cares, the most specific first.
+    fn irredundant(&self, mut on: Vec<BitCube>, dont_care: &[BitCube]) -> Vec<BitCube> {
+        on.sort_unstable_by(|a, b| b.literal_count().cmp(&a.literal_count()).then(a.cmp(b)));
+        let mut i = 0;
+        while i < on.len() {
+            let others: Vec<BitCube> = on
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, c)| c.clone())
+                .chain(dont_care.iter().cloned())
+                .collect();
+            if self.covers(&others, &on[i]) {
+                on.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        on
+    }
+
+    // Shrinks each cube to the smallest one still covering the rows no other cube does.
+    fn reduce(&self, mut on: Vec<BitCube>, dont_care: &[BitCube]) -> Vec<BitCube> {
+        on.sort_unstable_by(by_literal_count);
+        let mut i = 0;
+        while i < on.len() {
+            let others: Vec<BitCube> = on
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, c)| c.clone())
+                .chain(dont_care.iter().cloned())
+                .collect();
+            match self.complement_supercube(&self.cofactor(&others, &on[i])) {
+                Some(supercube) => {
+                    on[i] = on[i].intersect(&supercube);
+                    i += 1;
+                }
+                None => {
+                    on.remove(i);
+                }
+            }
+        }
+        on
+    }
+
+    fn minimize(&self, on: Vec<BitCube>, dont_care: &[BitCube]) -> Vec<BitCube> {
+        let off = self.complement(&[on.clone(), dont_care.to_vec()].concat());
+        let cost = |cover: &[BitCube]| {
+            let literals: u32 = cover.iter().map(BitCube::literal_count).sum();
+            (cover.len(), literals)
+        };
+        let mut best = self.irredundant(self.expand(on, &off), dont_care);
+        loop {
+            let reduced = self.reduce(best.clone(), dont_care);
+            let cover = self.irredundant(self.expand(reduced, &off), dont_care);
+            if cost(&cover) >= cost(&best) {
+                return best;
+            }
+            best = cover;
+        }
+    }
+}
+
+fn without_contained(mut cubes: Vec<BitCube>) -> Vec<BitCube> {
+    cubes.sort_unstable_by(by_literal_count);
+    cubes.dedup();
+    let mut kept: Vec<BitCube> = vec![];
+    for cube in cubes {
+        if !kept.iter().any(|k| k.contains(&cube)) {
+            kept.push(cube);
+        }
+    }
+    kept
+}
+
+// One past the highest variable index of the formula.
+fn variable_bound(node: &Node) -> usize {
+    match node {
+        Variable(v) => v.index + 1,
+        Constant(_) => 0,
+        UnaryExpr { child, .. } => variable_bound(child),
+        BinaryExpr { lhs, rhs, .. } => variable_bound(lhs).max(variable_bound(rhs)),
+    }
+}
+
+/// Small sum of products equal to `on` wherever `dont_care` is false, the rows where it is true
+/// taking whichever value gives the smaller result. Variable `i` of the formulas is
+/// `variables[i]`; `dont_care` may also use variables past them, a row being a don't care when
+/// it is true for some value of those.
+pub fn minimize(on: &Node, dont_care: &Node, variables: &[VariableRef]) -> Node {
+    let space = Space::new(variables.len());
+    let width = variable_bound(dont_care).max(variables.len());
+    let dont_care = space.project(&Space::new(width).cover_of(dont_care));
+    let cover = space.minimize(space.cover_of(on), &dont_care);
+    let cubes: Vec<Cube> = cover
+        .iter()
+        .map(|c| {
+            c.fixed()
+                .and(&space.full)
+                .variables()
+                .map(|v| Literal {
+                    variable: v,
+                    negated: !c.one.get(v),
+                })
+                .collect()
+        })
+        .collect();
+    terms_to_node(&canonical_terms(cubes), variables, And, Or)
+}
+
+#[cfg(test)]
+mod espresso_test {
+    use crate::assignment::Assignment;
+    use crate::cdcl;
+    use crate::cnf::{encode, Encoding};
+    use crate::espresso::minimize;
+    use crate::node::Node;
+    use crate::tree::Tree;
+    use std::str::FromStr;
+
+    fn assert_equal_on_care_set(tree: &Tree, dont_care: &Node, result: &Node) {
+        let count = tree.variable_count();
+        for bitfield in 0..1u64 << count {
+            let assignment = Assignment::from_bits(bitfield, count);
+            if !dont_care.eval_with(&assignment) {
+                assert_eq!(
+                    result.eval_with(&assignment),
+                    tree.root.eval_with(&assignment),
+                    "{}",
+                    tree.root.to_rpn()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_espresso_matches_truth_table() {
+        let formulas = [
+            "AB&C|",
+            "AB>C=",
+            "AB|C&D!^",
+            "AB^BC^&CA^&",
+            "ABC||D!&AB&!&",
+            "AB&A!B&|A!B!&|",
+            "AB^C^D^",
+            "AA!&",
+            "AA!|",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let variables = tree.variable_list.clone().unwrap_or_default();
+            let result = minimize(&tree.root, &Node::Constant(false), &variables);
+            assert_equal_on_care_set(&tree, &Node::Constant(false), &result);
+        }
+        let minimal = |formula: &str| {
+            let tree = Tree::from_str(formula).unwrap();
+            let variables = tree.variable_list.clone().unwrap_or_default();
+            minimize(&tree.root, &Node::Constant(false), &variables).to_rpn()
+        };
+        assert_eq!(minimal("AB&AB!&|"), "A");
+        assert_eq!(minimal("AB&A!B&|A!B!&|"), "A!B|");
+        assert_eq!(minimal("AA!&"), "0");
+        assert_eq!(minimal("AA!|"), "1");
+    }
+
+    #[test]
+    fn test_espresso_dont_care() {
+        // with A & !B free to be anything, A & B grows to A
+        let tree = Tree::from_str("AB&").unwrap();
+        let dont_care = Tree::from_str("AB!&").unwrap().root;
+        let result = tree.espresso(Some(&Tree::from_str("AB!&").unwrap()));
+        assert_eq!(result.root.to_rpn(), "A");
+        assert_equal_on_care_set(&tree, &dont_care, &result.root);
+
+        // `Y` is not a variable of the formula: A & !B is a don't care as Y may be true there
+        let result = tree.espresso(Some(&Tree::from_str("AB!&Y&").unwrap()));
+        assert_eq!(result.root.to_rpn(), "A");
+        let result = tree.espresso(Some(&Tree::from_str("YY!&").unwrap()));
+        assert_eq!(result.root.to_rpn(), "AB&");
+
+        // seven-segment style: rows 10 to 15 of a 4-bit input never occur
+        let names = ["A", "B", "C", "D"];
+        let digits: Vec<u64> = vec![0, 2, 3, 5, 6, 7, 8, 9];
+        let minterm = |row: u64| -> String {
+            let mut s = String::new();
+            for (i, name) in names.iter().enumerate() {
+                s.push_str(name);
+                if row & 1 << i == 0 {
+                    s.push('!');
+                }
+                if i > 0 {
+                    s.push('&');
+                }
+            }
+            s
+        };
+        let on: String =
+            digits.iter().map(|&d| minterm(d)).collect::<String>() + &"|".repeat(digits.len() - 1);
+        let dc: String = (10..16).map(minterm).collect::<String>() + &"|".repeat(5);
+        let tree = Tree::from_str(&on).unwrap();
+        let dont_care = Tree::from_str(&dc).unwrap();
+        let result = tree.espresso(Some(&dont_care));
+        assert_equal_on_care_set(&tree, &dont_care.root, &result.root);
+        let without = tree.espresso(None);
+        assert!(result.root.to_rpn().len() < without.root.to_rpn().len());
+    }
+
+    #[test]
+    fn test_espresso_wide() {
+        // 26 inputs, each pair term written twice split on `x`, and a redundant consensus term
+        let mut formula = String::new();
+        for i in 0..12 {
+            let pair = format!("[a{}][b{}]&", i, i);
+            formula.push_str(&format!("{}[x]&{}[x]!&|", pair, pair));
+            if i > 0 {
+                formula.push('|');
+            }
+        }
+        formula.push_str("[a0][b0]&[a1]&|");
+        let tree = Tree::from_str(&formula).unwrap();
+        let result = tree.espresso(None);
+        let rpn = result.root.to_rpn();
+        assert_eq!(rpn.matches('|').count(), 11, "{}", rpn);
+        assert_eq!(rpn.matches('&').count(), 12, "{}", rpn);
+
+        // no assignment tells the formulas apart
+        let count = tree.variable_count();
+        let miter = tree.root.clone() ^ result.root;
+        assert!(cdcl::solve(&encode(&miter, count, Encoding::Tseitin), count).is_none());
+    }
+
+    #[test]
+    fn test_espresso_past_128_variables() {
+        // a chain of 150 variables split on `x`, the last past the first two words of a cube
+        let chain: String =
+            (0..150).map(|i| format!("[v{}]", i)).collect::<String>() + &"&".repeat(149);
+        let formula = format!("{}[x]&{}[x]!&|", chain, chain);
+        let tree = Tree::from_str(&formula).unwrap();
+        let result = tree.espresso(None);
+        let rpn = result.root.to_rpn();
+        assert_eq!(rpn.matches('&').count(), 149, "{}", rpn);
+        assert!(!rpn.contains("[x]"), "{}", rpn);
+    }
+}
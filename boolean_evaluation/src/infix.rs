@@ -0,0 +1,252 @@
+use crate::error::ParseError;
+use crate::node::Node;
+use crate::operator::Operator;
+use crate::tree::{Tree, VariableTable};
+use Node::*;
+use Operator::*;
+use ParseError::*;
+
+/// Lexical unit shared with the RPN parser, which never produces parentheses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Variable(&'a str),
+    Constant(bool),
+    Operator(Operator),
+    OpenParen,
+    CloseParen,
+}
+
+/// Parses an infix formula into a [`Tree`].
+///
+/// Operators from tightest to loosest: `!`, `&`, `^`, `|`, `->` (or `>`), `<->` (or `=`).
+/// Their Unicode counterparts `¬`, `∧`, `⊕`, `∨`, `→`, `⇔` are accepted as well.
+/// Binary operators group to the left except `->`, which groups to the right.
+/// Variables are identifiers such as `A`, `feature_x` or `net_42`.
+pub fn parse(s: &str) -> Result<Tree, ParseError> {
+    let tokens = tokenize(s)?;
+    if tokens.is_empty() {
+        return Err(EmptyInput);
+    }
+
+    let mut parser = Parser {
+        variables: VariableTable::new(&tokens),
+        tokens,
+        pos: 0,
+    };
+    let root = parser.parse_expr(0)?;
+    if let Some(&(position, _)) = parser.tokens.get(parser.pos) {
+        return Err(UnexpectedToken { position });
+    }
+    Ok(Tree::from_parts(root, parser.variables))
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token<'_>)>, ParseError> {
+    let mut tokens = Vec::with_capacity(s.len());
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = position + 1;
+                while let Some((i, _)) =
+                    chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    end = i + 1;
+                }
+                Token::Variable(&s[position..end])
+            }
+            '1' => Token::Constant(true),
+            '0' => Token::Constant(false),
+            '!' | '¬' => Token::Operator(Not),
+            '∧' => Token::Operator(And),
+            '∨' => Token::Operator(Or),
+            '⊕' => Token::Operator(Xor),
+            '→' => Token::Operator(Imply),
+            '⇔' | '↔' => Token::Operator(Xnor),
+            '&' => Token::Operator(And),
+            '|' => Token::Operator(Or),
+            '^' => Token::Operator(Xor),
+            '>' => Token::Operator(Imply),
+            '=' => Token::Operator(Xnor),
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '-' if chars.next_if(|&(_, c)| c == '>').is_some() => Token::Operator(Imply),
+            '<' if chars.next_if(|&(_, c)| c == '-').is_some()
+                && chars.next_if(|&(_, c)| c == '>').is_some() =>
+            {
+                Token::Operator(Xnor)
+            }
+            _ => return Err(UnknownToken { position, token: c }),
+        };
+        tokens.push((position, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    pos: usize,
+    variables: VariableTable<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<(usize, Token<'a>)> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    // Precedence climbing: only binary operators binding at least as tightly as `min_precedence`
+    // are folded into the current operand.
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<Node, ParseError> {
+        let mut lhs = self.parse_operand()?;
+
+        while let Some(&(_, Token::Operator(op))) = self.tokens.get(self.pos) {
+            if op == Not || op.precedence() < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let next_precedence = if op.is_right_associative() {
+                op.precedence()
+            } else {
+                op.precedence() + 1
+            };
+            let rhs = self.parse_expr(next_precedence)?;
+            lhs = BinaryExpr {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn parse_operand(&mut self) -> Result<Node, ParseError> {
+        match self.next() {
+            Some((_, Token::Variable(name))) => Ok(self.variables.node(name)),
+            Some((_, Token::Constant(x))) => Ok(Constant(x)),
+            Some((_, Token::Operator(Not))) => Ok(UnaryExpr {
+                op: Not,
+                child: Box::new(self.parse_operand()?),
+            }),
+            Some((position, Token::OpenParen)) => {
+                let node = self.parse_expr(0)?;
+                match self.next() {
+                    Some((_, Token::CloseParen)) => Ok(node),
+                    Some((position, _)) => Err(UnexpectedToken { position }),
+                    None => Err(UnclosedParenthesis { position }),
+                }
+            }
+            Some((position, Token::Operator(operator))) => {
+                Err(MissingOperands { position, operator })
+            }
+            Some((position, _)) => Err(UnexpectedToken { position }),
+            None => Err(UnexpectedEnd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod infix_test {
+    use crate::error::ParseError::*;
+    use crate::operator::Operator;
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    fn assert_same_tree(infix: &str, rpn: &str) {
+        let node = Tree::from_infix(infix).unwrap();
+        let result = Tree::from_str(rpn).unwrap();
+        assert_eq!(node.root, result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
+    #[test]
+    fn test_infix_basic() {
+        assert_same_tree("A & B", "AB&");
+        assert_same_tree("A | B", "AB|");
+        assert_same_tree("A ^ B", "AB^");
+        assert_same_tree("A -> B", "AB>");
+        assert_same_tree("A <-> B", "AB=");
+        assert_same_tree("!A", "A!");
+        assert_eq!(
+            Tree::from_infix("!!1").unwrap().root,
+            Tree::from_str("1!!").unwrap().root
+        );
+    }
+
+    #[test]
+    fn test_infix_precedence() {
+        assert_same_tree("A & B | !C", "AB&C!|");
+        assert_same_tree("A | B & C", "ABC&|");
+        assert_same_tree("A ^ B | C ^ D", "AB^CD^|");
+        assert_same_tree("A | B -> C", "AB|C>");
+        assert_same_tree("A -> B <-> C", "AB>C=");
+        assert_same_tree("!A & B", "A!B&");
+    }
+
+    #[test]
+    fn test_infix_associativity() {
+        assert_same_tree("A & B & C", "AB&C&");
+        assert_same_tree("A | B | C | D", "AB|C|D|");
+        assert_same_tree("A -> B -> C", "ABC>>");
+    }
+
+    #[test]
+    fn test_infix_parentheses() {
+        assert_same_tree("(A & B) | !C", "AB&C!|");
+        assert_same_tree("A & (B | C)", "ABC|&");
+        assert_same_tree("!(A | B)", "AB|!");
+        assert_same_tree("((A))", "A");
+        assert_same_tree("(A -> B) -> C", "AB>C>");
+    }
+
+    #[test]
+    fn test_infix_unicode() {
+        assert_same_tree("(A ∧ B) ∨ ¬C", "AB&C!|");
+        assert_same_tree("A ⊕ B → C ⇔ D", "AB^C>D=");
+    }
+
+    #[test]
+    fn test_infix_invalid() {
+        assert_eq!(Tree::from_infix("").unwrap_err(), EmptyInput);
+        assert_eq!(Tree::from_infix("   ").unwrap_err(), EmptyInput);
+        assert_eq!(
+            Tree::from_infix("A B").unwrap_err(),
+            UnexpectedToken { position: 2 }
+        );
+        assert_eq!(Tree::from_infix("A &").unwrap_err(), UnexpectedEnd);
+        assert_eq!(
+            Tree::from_infix("& A").unwrap_err(),
+            MissingOperands {
+                position: 0,
+                operator: Operator::And
+            }
+        );
+        assert_eq!(
+            Tree::from_infix("(A | B").unwrap_err(),
+            UnclosedParenthesis { position: 0 }
+        );
+        assert_eq!(
+            Tree::from_infix("A | B)").unwrap_err(),
+            UnexpectedToken { position: 5 }
+        );
+        assert_eq!(
+            Tree::from_infix("A - B").unwrap_err(),
+            UnknownToken {
+                position: 2,
+                token: '-'
+            }
+        );
+        assert!(Tree::from_infix("A <- B").is_err());
+        assert!(Tree::from_infix("A & 4x").is_err());
+    }
+
+    #[test]
+    fn test_infix_names() {
+        assert_same_tree("feature_x & !net_42", "[feature_x][net_42]!&");
+        assert_same_tree("(a|_b)->A", "[a][_b]|A>");
+        assert_same_tree("AB | A", "[AB]A|");
+    }
+}
@@ -1,8 +1,27 @@
+pub mod allsat;
+pub mod assignment;
+pub mod bdd;
+pub mod big_uint;
+pub mod cdcl;
 pub mod cnf;
+pub mod count;
+pub mod diff;
+pub mod dimacs;
+pub mod dnf;
+pub mod dpll;
+pub mod error;
+pub mod espresso;
+pub mod infix;
+pub mod literal;
+pub mod model;
 pub mod nnf;
 pub mod node;
+mod normal_form;
 pub mod operator;
+pub mod quine_mccluskey;
 mod set;
+pub mod simplify;
 pub mod tree;
 pub mod truth_table;
 mod variable;
+pub mod zdd;
@@ -0,0 +1,117 @@
+use crate::node::Node;
+use crate::operator::Operator;
+use crate::tree::VariableRef;
+use std::ops;
+
+/// A variable, by index in the tree's variable list, or its negation.
+///
+/// Literals order by variable first, so `A < ¬A < B`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    pub variable: usize,
+    pub negated: bool,
+}
+
+/// A disjunction of literals, the empty clause being false.
+pub type Clause = Vec<Literal>;
+
+/// A conjunction of literals, the empty cube being true.
+pub type Cube = Vec<Literal>;
+
+impl Literal {
+    pub fn positive(variable: usize) -> Self {
+        Self {
+            variable,
+            negated: false,
+        }
+    }
+
+    pub fn negative(variable: usize) -> Self {
+        Self {
+            variable,
+            negated: true,
+        }
+    }
+
+    /// Value of the literal when its variable is `value`.
+    pub fn eval(self, value: bool) -> bool {
+        value != self.negated
+    }
+}
+
+impl ops::Not for Literal {
+    type Output = Literal;
+
+    fn not(self) -> Literal {
+        Self {
+            variable: self.variable,
+            negated: !self.negated,
+        }
+    }
+}
+
+/// Canonical form of the clauses of a CNF or the cubes of a DNF: literals sorted and distinct
+/// within each term, no term with both `x` and `!x`, no term containing another, terms sorted.
+pub(crate) fn canonical_terms(terms: Vec<Vec<Literal>>) -> Vec<Vec<Literal>> {
+    let mut terms: Vec<Vec<Literal>> = terms
+        .into_iter()
+        .filter_map(|mut term| {
+            term.sort_unstable();
+            term.dedup();
+            // sorted, `x` and `!x` sit next to each other
+            let complementary = term.windows(2).any(|w| w[0].variable == w[1].variable);
+            (!complementary).then_some(term)
+        })
+        .collect();
+    // shortest first, so a term can only be subsumed by one already kept
+    terms.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    terms.dedup();
+    let mut kept: Vec<Vec<Literal>> = vec![];
+    for term in terms {
+        if !kept.iter().any(|k| is_subset(k, &term)) {
+            kept.push(term);
+        }
+    }
+    kept.sort_unstable();
+    kept
+}
+
+// Both sorted.
+fn is_subset(small: &[Literal], large: &[Literal]) -> bool {
+    let mut large = large.iter();
+    small.iter().all(|l| large.any(|m| m == l))
+}
+
+/// Joins the literals of each term with `inner` and the terms with `outer`, nested to the
+/// right. Literal `i` is variable `variables[i]`.
+pub(crate) fn terms_to_node(
+    terms: &[Vec<Literal>],
+    variables: &[VariableRef],
+    inner: Operator,
+    outer: Operator,
+) -> Node {
+    let literal = |l: &Literal| {
+        let node = Node::Variable(variables[l.variable].clone());
+        if l.negated {
+            !node
+        } else {
+            node
+        }
+    };
+    let terms = terms
+        .iter()
+        .map(|term| join(term.iter().map(literal), inner));
+    join(terms, outer)
+}
+
+// `1` for an empty conjunction, `0` for an empty disjunction.
+fn join(nodes: impl DoubleEndedIterator<Item = Node>, op: Operator) -> Node {
+    nodes
+        .rev()
+        .reduce(|rhs, lhs| Node::BinaryExpr {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        })
+        .unwrap_or(Node::Constant(op == Operator::And))
+}
@@ -0,0 +1,53 @@
+use crate::assignment::Assignment;
+use crate::truth_table::format_table;
+use std::fmt::{Display, Formatter};
+
+/// Values of a formula's variables, by name, as found by a solver.
+///
+/// [`Model::assignment`] feeds straight back into [`Node::eval_with`](crate::node::Node::eval_with)
+/// to check the answer, and [`Display`] prints it as a one row truth table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    variables: Vec<String>,
+    assignment: Assignment,
+}
+
+impl Model {
+    pub fn new(variables: Vec<String>, assignment: Assignment) -> Self {
+        Self {
+            variables,
+            assignment,
+        }
+    }
+
+    pub fn assignment(&self) -> &Assignment {
+        &self.assignment
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Value of variable `name`, `None` when the formula has no such variable.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.variables
+            .iter()
+            .position(|v| v == name)
+            .map(|i| self.assignment.get(i))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.variables
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.as_str(), self.assignment.get(i)))
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let columns: Vec<&str> = self.variables.iter().map(String::as_str).collect();
+        let row: Vec<bool> = (0..columns.len()).map(|i| self.assignment.get(i)).collect();
+        write!(f, "{}", format_table(&columns, &[row]))
+    }
+}
@@ -42,7 +42,7 @@ pub fn unary_expr_to_nnf(node: Node) -> Node {
                 let lhs = *lhs;
                 let rhs = *rhs;
                 node_to_nnf(match op {
-                    Imply => !lhs | rhs,
+                    Imply => lhs & !rhs,
                     Xnor => binary_expr_to_nnf(lhs ^ rhs),
                     And => !lhs | !rhs,
                     Or => !lhs & !rhs,
@@ -85,6 +85,14 @@ mod nnf_test {
         assert_eq!(TruthTable::from(node), TruthTable::from(result));
     }
 
+    #[test]
+    fn test_nnf_not_imply() {
+        let node = Tree::from_str("AB>!").unwrap();
+        let result = Tree::from_str("AB!&").unwrap();
+        assert_eq!(node_to_nnf(node.root.clone()), result.root);
+        assert_eq!(TruthTable::from(node), TruthTable::from(result));
+    }
+
     #[test]
     fn test_nnf_xnor() {
         let node = Tree::from_str("AB=").unwrap();
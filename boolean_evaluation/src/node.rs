@@ -1,16 +1,16 @@
-use crate::operator::Operator;
+use crate::assignment::Assignment;
+use crate::operator::{Notation, Operator};
 use crate::set::Set;
 use crate::variable::Variable;
-use std::cell::RefCell;
 use std::fmt::{Display, Formatter};
-use std::rc::Rc;
+use std::sync::Arc;
 use std::{fmt, ops};
 use Node::*;
 use Operator::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Node {
-    Variable(Rc<RefCell<Variable>>),
+    Variable(Arc<Variable>),
     Constant(bool),
     UnaryExpr {
         op: Operator,
@@ -25,63 +25,78 @@ pub enum Node {
 
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let mut ret = String::new();
-
-        match self {
-            Variable(c) => ret.push(RefCell::borrow(c).name),
-            Constant(x) => ret.push(if *x { '1' } else { '0' }),
-            UnaryExpr { op, child } => ret.push_str(&*format!("{}{}", op, child)),
-            BinaryExpr { op, lhs, rhs } => ret.push_str(&*format!("{} {} {}", lhs, op, rhs)),
-        }
-        write!(f, "{}", ret)
+        write!(f, "{}", self.to_infix(Notation::Unicode))
     }
 }
 
 impl Node {
-    pub fn eval(self) -> bool {
+    /// Evaluates the formula with variable values taken from `assignment`, leaving the tree
+    /// untouched.
+    pub fn eval_with(&self, assignment: &Assignment) -> bool {
         match self {
-            Variable(v) => v.borrow().value,
-            Constant(p) => p,
-            BinaryExpr { op, lhs, rhs } => op.eval_binary(lhs.eval(), rhs.eval()),
-            UnaryExpr { op, child } => op.eval_unary(child.eval()),
-        }
-    }
-
-    pub fn eval_ref(&self) -> bool {
-        match self {
-            Variable(v) => v.borrow().value,
+            Variable(v) => assignment.get(v.index),
             Constant(p) => *p,
-            BinaryExpr { op, lhs, rhs } => op.eval_binary(lhs.eval_ref(), rhs.eval_ref()),
-            UnaryExpr { op, child } => op.eval_unary(child.eval_ref()),
+            BinaryExpr { op, lhs, rhs } => {
+                op.eval_binary(lhs.eval_with(assignment), rhs.eval_with(assignment))
+            }
+            UnaryExpr { op, child } => op.eval_unary(child.eval_with(assignment)),
         }
     }
 
-    pub fn compute_sets(&self) -> Set {
+    /// Evaluates the formula over sets, `sets` holding one set per variable index.
+    pub fn compute_sets(&self, sets: &[Vec<i32>]) -> Set {
         match self {
-            Variable(v) => Set::from(v.borrow().set.as_ref().unwrap().clone()),
+            Variable(v) => Set::from(sets[v.index].clone()),
             Constant(p) => Set::from(vec![if *p { 1 } else { 0 }]),
             BinaryExpr { op, lhs, rhs } => {
-                let lhs_sets = lhs.compute_sets();
-                let rhs_sets = rhs.compute_sets();
+                let lhs_sets = lhs.compute_sets(sets);
+                let rhs_sets = rhs.compute_sets(sets);
                 op.eval_binary_sets(lhs_sets, rhs_sets)
             }
             UnaryExpr { op, child } => {
-                let child_sets = child.compute_sets();
+                let child_sets = child.compute_sets(sets);
                 op.eval_unary_sets(child_sets)
             }
         }
     }
 
+    /// Same formula with each variable replaced by `variable(v)`.
+    pub(crate) fn map_variables(
+        &self,
+        variable: &impl Fn(&Arc<Variable>) -> Arc<Variable>,
+    ) -> Node {
+        match self {
+            Variable(v) => Variable(variable(v)),
+            Constant(p) => Constant(*p),
+            BinaryExpr { op, lhs, rhs } => BinaryExpr {
+                op: *op,
+                lhs: Box::new(lhs.map_variables(variable)),
+                rhs: Box::new(rhs.map_variables(variable)),
+            },
+            UnaryExpr { op, child } => UnaryExpr {
+                op: *op,
+                child: Box::new(child.map_variables(variable)),
+            },
+        }
+    }
+
     pub fn to_rpn(&self) -> String {
         let mut ret = String::new();
         match self {
-            // for a variable or constant, just print the value
-            Variable(c) => ret.push(RefCell::borrow(c).name),
+            // for a variable or constant, just print the value, longer names go between brackets
+            Variable(c) => {
+                let name = &c.name;
+                if matches!(name.as_bytes(), [b'A'..=b'Z']) {
+                    ret.push_str(name);
+                } else {
+                    ret.push_str(&format!("[{}]", name));
+                }
+            }
             Constant(x) => ret.push(if *x { '1' } else { '0' }),
             // for a unary expression, first recurse on the child, then print the operator
-            UnaryExpr { op, child } => ret.push_str(&*format!("{}{:?}", Self::to_rpn(child), op)),
+            UnaryExpr { op, child } => ret.push_str(&format!("{}{:?}", Self::to_rpn(child), op)),
             // for a binary expression, first recurse on the lhs, then recurse on the rhs, then print the operator
-            BinaryExpr { op, lhs, rhs } => ret.push_str(&*format!(
+            BinaryExpr { op, lhs, rhs } => ret.push_str(&format!(
                 "{}{}{:?}",
                 Self::to_rpn(lhs),
                 Self::to_rpn(rhs),
@@ -90,6 +105,46 @@ impl Node {
         }
         ret
     }
+
+    /// Prints the formula in infix notation with only the parentheses needed to parse it back
+    /// into the same tree with [`Tree::from_infix`](crate::tree::Tree::from_infix).
+    pub fn to_infix(&self, notation: Notation) -> String {
+        match self {
+            Variable(c) => c.name.clone(),
+            Constant(x) => String::from(if *x { "1" } else { "0" }),
+            UnaryExpr { op, child } => match **child {
+                BinaryExpr { .. } => {
+                    format!("{}({})", op.symbol(notation), child.to_infix(notation))
+                }
+                _ => format!("{}{}", op.symbol(notation), child.to_infix(notation)),
+            },
+            BinaryExpr { op, lhs, rhs } => format!(
+                "{} {} {}",
+                Self::operand_to_infix(lhs, *op, !op.is_right_associative(), notation),
+                op.symbol(notation),
+                Self::operand_to_infix(rhs, *op, op.is_right_associative(), notation)
+            ),
+        }
+    }
+
+    // An operand only goes between parentheses when it binds looser than its parent, or as tight
+    // but on the side the parent does not group towards.
+    fn operand_to_infix(
+        operand: &Node,
+        parent: Operator,
+        groups_here: bool,
+        notation: Notation,
+    ) -> String {
+        match operand {
+            BinaryExpr { op, .. }
+                if op.precedence() < parent.precedence()
+                    || (op.precedence() == parent.precedence() && !groups_here) =>
+            {
+                format!("({})", operand.to_infix(notation))
+            }
+            _ => operand.to_infix(notation),
+        }
+    }
 }
 
 impl ops::BitOr for Node {
@@ -141,6 +196,8 @@ impl ops::BitXor for Node {
 
 #[cfg(test)]
 mod node_tests {
+    use crate::assignment::Assignment;
+    use crate::operator::Notation;
     use crate::tree::Tree;
     use std::str::FromStr;
 
@@ -172,4 +229,68 @@ mod node_tests {
         let node = Tree::from_str(teststr).unwrap();
         assert_eq!(node.root.to_rpn(), teststr);
     }
+
+    #[test]
+    fn test_to_rpn_names() {
+        let teststr = "[feature_x]B&[net_42]|!";
+        let node = Tree::from_str(teststr).unwrap();
+        assert_eq!(node.root.to_rpn(), teststr);
+    }
+
+    #[test]
+    fn test_to_infix() {
+        let cases = [
+            ("AB|C&", "(A | B) & C", "(A ∨ B) ∧ C"),
+            ("ABC&|", "A | B & C", "A ∨ B ∧ C"),
+            ("AB&C&", "A & B & C", "A ∧ B ∧ C"),
+            ("ABC&&", "A & (B & C)", "A ∧ (B ∧ C)"),
+            ("ABC>>", "A -> B -> C", "A → B → C"),
+            ("AB>C>", "(A -> B) -> C", "(A → B) → C"),
+            ("AB=C!D^|!", "!((A <-> B) | !C ^ D)", "¬((A ⇔ B) ∨ ¬C ⊕ D)"),
+            ("A!!1&", "!!A & 1", "¬¬A ∧ 1"),
+        ];
+        for (rpn, ascii, unicode) in cases {
+            let node = Tree::from_str(rpn).unwrap().root;
+            assert_eq!(node.to_infix(Notation::Ascii), ascii);
+            assert_eq!(node.to_infix(Notation::Unicode), unicode);
+            assert_eq!(node.to_string(), unicode);
+        }
+    }
+
+    #[test]
+    fn test_to_infix_round_trip() {
+        let cases = [
+            "AB|C&",
+            "ABC|&D>",
+            "AB>C>D>",
+            "ABC=>!",
+            "AB^C^DE^^",
+            "[feature_x]B&[net_42]|!",
+        ];
+        for rpn in cases {
+            let node = Tree::from_str(rpn).unwrap().root;
+            for notation in [Notation::Ascii, Notation::Unicode] {
+                let printed = node.to_infix(notation);
+                assert_eq!(Tree::from_infix(&printed).unwrap().root, node);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_with() {
+        let tree = Tree::from_str("AB&C|").unwrap();
+        let assignments = [
+            (vec![false, false, false], false),
+            (vec![true, true, false], true),
+            (vec![true, false, false], false),
+            (vec![false, false, true], true),
+        ];
+        for (values, expected) in assignments {
+            assert_eq!(tree.root.eval_with(&Assignment::from(values)), expected);
+        }
+        // the tree is left as is and can be evaluated again
+        assert!(tree
+            .root
+            .eval_with(&Assignment::from(vec![true, true, false])));
+    }
 }
@@ -0,0 +1,172 @@
+//! Conversion shared by the conjunctive and disjunctive normal forms, which are each other's
+//! dual: a CNF is an `And` of `Or` terms, a DNF an `Or` of `And` terms. Every function takes the
+//! `outer` and `inner` operators, `(And, Or)` for a CNF and `(Or, And)` for a DNF.
+
+use crate::literal::Literal;
+use crate::nnf::node_to_nnf;
+use crate::node::Node;
+use crate::operator::Operator;
+use Node::*;
+use Operator::*;
+
+fn binary(op: Operator, lhs: Node, rhs: Node) -> Node {
+    BinaryExpr {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    }
+}
+
+fn is_outer(node: &Node, outer: Operator) -> bool {
+    matches!(node, BinaryExpr { op, .. } if *op == outer)
+}
+
+/// `outer` of `inner` of literals, equivalent to `node`.
+pub(crate) fn normal_form(node: Node, outer: Operator, inner: Operator) -> Node {
+    let node = node_to_nnf(node);
+    let BinaryExpr { op, lhs, rhs } = node else {
+        return node;
+    };
+    let lhs = normal_form(*lhs, outer, inner);
+    let rhs = normal_form(*rhs, outer, inner);
+    if op == outer {
+        return binary(outer, lhs, rhs);
+    }
+    // look at the converted operands, a nested `inner` can turn into an `outer`
+    let has_outer = (is_outer(&lhs, outer), is_outer(&rhs, outer));
+    let node = binary(inner, lhs, rhs);
+    match has_outer {
+        (true, true) => distribute_both(node, outer, inner),
+        (true, false) => distribute_to_left(node, outer, inner),
+        (false, true) => distribute_to_right(node, outer, inner),
+        _ => node,
+    }
+}
+
+// Distribute `inner` over `outer` on both sides.
+pub(crate) fn distribute_both(node: Node, outer: Operator, inner: Operator) -> Node {
+    let BinaryExpr { lhs, rhs, .. } = node else {
+        unreachable!()
+    };
+    match (*lhs, *rhs) {
+        (
+            BinaryExpr {
+                lhs: lhs_lhs,
+                rhs: lhs_rhs,
+                ..
+            },
+            BinaryExpr {
+                lhs: rhs_lhs,
+                rhs: rhs_rhs,
+                ..
+            },
+        ) => {
+            let lhs_lhs = normal_form(*lhs_lhs, outer, inner);
+            let lhs_rhs = normal_form(*lhs_rhs, outer, inner);
+            let rhs_lhs = normal_form(*rhs_lhs, outer, inner);
+            let rhs_rhs = normal_form(*rhs_rhs, outer, inner);
+            // ((ll . rl) + (ll . rr)) + ((lr . rl) + (lr . rr)) with `+` outer and `.` inner, each
+            // inner term distributed again in case its operands are outer themselves
+            let term = |lhs: Node, rhs: Node| normal_form(binary(inner, lhs, rhs), outer, inner);
+            binary(
+                outer,
+                binary(
+                    outer,
+                    term(lhs_lhs.clone(), rhs_lhs.clone()),
+                    term(lhs_lhs, rhs_rhs.clone()),
+                ),
+                binary(
+                    outer,
+                    term(lhs_rhs.clone(), rhs_lhs),
+                    term(lhs_rhs, rhs_rhs),
+                ),
+            )
+        }
+        _ => unreachable!(),
+    }
+}
+
+pub(crate) fn distribute_to_left(node: Node, outer: Operator, inner: Operator) -> Node {
+    let BinaryExpr { lhs, rhs, .. } = node else {
+        unreachable!()
+    };
+    match *lhs {
+        BinaryExpr {
+            lhs: lhs_lhs,
+            rhs: lhs_rhs,
+            ..
+        } => binary(
+            outer,
+            normal_form(binary(inner, *lhs_lhs, *rhs.clone()), outer, inner),
+            normal_form(binary(inner, *lhs_rhs, *rhs), outer, inner),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+pub(crate) fn distribute_to_right(node: Node, outer: Operator, inner: Operator) -> Node {
+    let BinaryExpr { lhs, rhs, .. } = node else {
+        unreachable!()
+    };
+    match *rhs {
+        BinaryExpr {
+            lhs: rhs_lhs,
+            rhs: rhs_rhs,
+            ..
+        } => binary(
+            outer,
+            normal_form(binary(inner, *lhs.clone(), *rhs_lhs), outer, inner),
+            normal_form(binary(inner, *lhs, *rhs_rhs), outer, inner),
+        ),
+        _ => unreachable!(),
+    }
+}
+
+/// Collects the terms of a formula in normal form, such as the output of [`normal_form`].
+/// Terms holding the constant that decides `inner`, `1` in a clause and `0` in a cube, are
+/// dropped and the other constant is left out.
+pub(crate) fn collect_terms(node: &Node, outer: Operator, inner: Operator) -> Vec<Vec<Literal>> {
+    let mut terms = vec![];
+    collect(node, outer, inner, &mut terms);
+    terms
+}
+
+fn collect(node: &Node, outer: Operator, inner: Operator, terms: &mut Vec<Vec<Literal>>) {
+    match node {
+        BinaryExpr { op, lhs, rhs } if *op == outer => {
+            collect(lhs, outer, inner, terms);
+            collect(rhs, outer, inner, terms);
+        }
+        _ => {
+            let mut term = vec![];
+            if collect_literals(node, inner, &mut term) {
+                terms.push(term);
+            }
+        }
+    }
+}
+
+// Returns false once the term is known to be decided by a constant.
+fn collect_literals(node: &Node, inner: Operator, term: &mut Vec<Literal>) -> bool {
+    // `1` decides a disjunction, `0` a conjunction
+    let deciding = inner == Or;
+    match node {
+        BinaryExpr { op, lhs, rhs } if *op == inner => {
+            collect_literals(lhs, inner, term) && collect_literals(rhs, inner, term)
+        }
+        Variable(v) => {
+            term.push(Literal::positive(v.index));
+            true
+        }
+        Constant(x) => *x != deciding,
+        UnaryExpr { op: Not, child } => match &**child {
+            Variable(v) => {
+                term.push(Literal::negative(v.index));
+                true
+            }
+            Constant(x) => *x == deciding,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use Operator::*;
 use std::fmt::{Debug, Display, Formatter};
 
-#[derive(Clone, PartialEq, Eq, Copy)]
+#[derive(Clone, PartialEq, Eq, Hash, Copy)]
 pub enum Operator {
     Not,
     And,
@@ -13,16 +13,18 @@ pub enum Operator {
     Xnor,
 }
 
+/// Operator symbols used when printing infix formulas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// `!`, `&`, `|`, `^`, `->`, `<->`
+    Ascii,
+    /// `¬`, `∧`, `∨`, `⊕`, `→`, `⇔`
+    Unicode,
+}
+
 impl Display for Operator {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Not => write!(f, "¬"),
-            And => write!(f, "∧"),
-            Or => write!(f, "∨"),
-            Xor => write!(f, "⊕"),
-            Imply => write!(f, "→"),
-            Xnor => write!(f, "⇔"),
-        }
+        write!(f, "{}", self.symbol(Notation::Unicode))
     }
 }
 
@@ -39,7 +41,57 @@ impl Debug for Operator {
     }
 }
 
+impl TryFrom<char> for Operator {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '!' => Ok(Not),
+            '&' => Ok(And),
+            '|' => Ok(Or),
+            '^' => Ok(Xor),
+            '>' => Ok(Imply),
+            '=' => Ok(Xnor),
+            _ => Err(c),
+        }
+    }
+}
+
 impl Operator {
+    pub fn symbol(self, notation: Notation) -> &'static str {
+        match (notation, self) {
+            (Notation::Ascii, Not) => "!",
+            (Notation::Ascii, And) => "&",
+            (Notation::Ascii, Or) => "|",
+            (Notation::Ascii, Xor) => "^",
+            (Notation::Ascii, Imply) => "->",
+            (Notation::Ascii, Xnor) => "<->",
+            (Notation::Unicode, Not) => "¬",
+            (Notation::Unicode, And) => "∧",
+            (Notation::Unicode, Or) => "∨",
+            (Notation::Unicode, Xor) => "⊕",
+            (Notation::Unicode, Imply) => "→",
+            (Notation::Unicode, Xnor) => "⇔",
+        }
+    }
+
+    /// Binding strength in infix notation, higher binds tighter.
+    pub fn precedence(self) -> u8 {
+        match self {
+            Not => 6,
+            And => 5,
+            Xor => 4,
+            Or => 3,
+            Imply => 2,
+            Xnor => 1,
+        }
+    }
+
+    /// `A -> B -> C` reads as `A -> (B -> C)`, every other binary operator groups to the left.
+    pub fn is_right_associative(self) -> bool {
+        matches!(self, Imply)
+    }
+
     pub fn eval_binary(self, lhs: bool, rhs: bool) -> bool {
         match self {
             Imply => !lhs | rhs,
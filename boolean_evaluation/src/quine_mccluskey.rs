@@ -0,0 +1,207 @@
+//! Two-level minimisation: the prime implicants of a function are found with the Quine–McCluskey
+//! procedure, then the smallest set of them covering every true row is picked with Petrick's
+//! method.
+
+use crate::literal::{canonical_terms, terms_to_node, Cube, Literal};
+use crate::operator::Operator::{And, Or};
+use crate::tree::Tree;
+use crate::truth_table::TruthTable;
+use std::collections::{BTreeSet, HashSet};
+
+/// Rows matched by fixing the bits outside `mask` to those of `value`, bit `i` being variable
+/// `i`. Bits of `value` inside the mask are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Implicant {
+    value: u64,
+    mask: u64,
+}
+
+impl Implicant {
+    fn covers(&self, row: u64) -> bool {
+        row & !self.mask == self.value
+    }
+
+    // Merges two implicants differing in a single fixed bit.
+    fn merge(&self, other: &Implicant) -> Option<Implicant> {
+        let difference = self.value ^ other.value;
+        (self.mask == other.mask && difference.count_ones() == 1).then_some(Implicant {
+            value: self.value & !difference,
+            mask: self.mask | difference,
+        })
+    }
+
+    fn literal_count(&self, variable_count: usize) -> usize {
+        variable_count - self.mask.count_ones() as usize
+    }
+
+    fn cube(&self, variable_count: usize) -> Cube {
+        (0..variable_count)
+            .filter(|v| self.mask & 1 << v == 0)
+            .map(|v| Literal {
+                variable: v,
+                negated: self.value & 1 << v == 0,
+            })
+            .collect()
+    }
+}
+
+/// Smallest sum of products equal to `table`: no sum with fewer products exists, and none with
+/// as many products and fewer literals.
+pub fn minimize(table: &TruthTable) -> Tree {
+    let variable_count = table.variables().len();
+    let minterms: Vec<u64> = table
+        .outputs()
+        .enumerate()
+        .filter(|(_, output)| *output)
+        .map(|(row, _)| row as u64)
+        .collect();
+    let primes = prime_implicants(&minterms);
+    let cubes: Vec<Cube> = cover(&primes, &minterms, variable_count)
+        .into_iter()
+        .map(|i| primes[i].cube(variable_count))
+        .collect();
+    let cubes = canonical_terms(cubes);
+    Tree::from_names(table.variables(), |variables| {
+        terms_to_node(&cubes, variables, And, Or)
+    })
+}
+
+// Merges implicants pairwise until nothing merges, keeping those that never did.
+fn prime_implicants(minterms: &[u64]) -> Vec<Implicant> {
+    let mut primes = vec![];
+    let mut current: Vec<Implicant> = minterms
+        .iter()
+        .map(|&value| Implicant { value, mask: 0 })
+        .collect();
+    while !current.is_empty() {
+        let mut merged = vec![false; current.len()];
+        let mut next = HashSet::new();
+        for i in 0..current.len() {
+            for j in i + 1..current.len() {
+                if let Some(implicant) = current[i].merge(&current[j]) {
+                    next.insert(implicant);
+                    merged[i] = true;
+                    merged[j] = true;
+                }
+            }
+        }
+        primes.extend(
+            (0..current.len())
+                .filter(|&i| !merged[i])
+                .map(|i| current[i]),
+        );
+        current = next.into_iter().collect();
+        current.sort_unstable();
+    }
+    primes
+}
+
+// Indices of the fewest primes covering every minterm, fewest literals breaking ties.
+fn cover(primes: &[Implicant], minterms: &[u64], variable_count: usize) -> Vec<usize> {
+    let covering: Vec<Vec<usize>> = minterms
+        .iter()
+        .map(|&m| (0..primes.len()).filter(|&i| primes[i].covers(m)).collect())
+        .collect();
+    // a minterm covered by a single prime makes it essential
+    let essential: BTreeSet<usize> = covering
+        .iter()
+        .filter(|c| c.len() == 1)
+        .map(|c| c[0])
+        .collect();
+
+    // Petrick's method: multiply out the product of the sums of primes covering each minterm
+    // left, dropping products that contain another
+    let mut products: Vec<BTreeSet<usize>> = vec![BTreeSet::new()];
+    for sum in covering
+        .iter()
+        .filter(|c| !c.iter().any(|p| essential.contains(p)))
+    {
+        let mut next: Vec<BTreeSet<usize>> = vec![];
+        for product in products {
+            if sum.iter().any(|p| product.contains(p)) {
+                next.push(product);
+                continue;
+            }
+            for &p in sum {
+                let mut product = product.clone();
+                product.insert(p);
+                next.push(product);
+            }
+        }
+        next.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        next.dedup();
+        products = vec![];
+        for product in next {
+            if !products.iter().any(|p| p.is_subset(&product)) {
+                products.push(product);
+            }
+        }
+    }
+    let cost = |product: &BTreeSet<usize>| {
+        let literals: usize = product
+            .iter()
+            .map(|&p| primes[p].literal_count(variable_count))
+            .sum();
+        (product.len(), literals)
+    };
+    let best = products.into_iter().min_by_key(cost).unwrap();
+    essential.into_iter().chain(best).collect()
+}
+
+#[cfg(test)]
+mod quine_mccluskey_test {
+    use crate::quine_mccluskey::minimize;
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_minimize_matches_truth_table() {
+        let formulas = [
+            "AB&C|",
+            "AB>C=",
+            "AB|C&D!^",
+            "AB^BC^&CA^&",
+            "ABC||D!&AB&!&",
+            "AB&A!B&|A!B!&|",
+            "AA!&",
+            "AA!|",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            let minimal = tree.minimize();
+            assert_eq!(
+                TruthTable::from(minimal),
+                TruthTable::from(tree),
+                "{}",
+                formula
+            );
+        }
+    }
+
+    #[test]
+    fn test_minimize_results() {
+        let minimal = |formula: &str| Tree::from_str(formula).unwrap().minimize().root.to_rpn();
+        assert_eq!(minimal("AB&AB!&|"), "A");
+        assert_eq!(minimal("AB&A|B|"), "AB|");
+        assert_eq!(minimal("AB&A!B&|A!B!&|"), "A!B|");
+        assert_eq!(minimal("AB&!AB!|&"), "B!");
+        assert_eq!(minimal("AA!&"), "0");
+        assert_eq!(minimal("AA!|"), "1");
+        assert_eq!(minimal("1A!!&"), "A");
+        assert_eq!(minimal("10|"), "1");
+    }
+
+    #[test]
+    fn test_minimize_cyclic() {
+        // rows 0, 1, 2, 5, 6, 7 of three variables: six primes, none essential, and two covers
+        // of three primes each
+        let names = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let table = TruthTable::new(names, &[true, true, true, false, false, true, true, true]);
+        let tree = minimize(&table);
+        let rpn = tree.root.to_rpn();
+        assert_eq!(rpn.matches('|').count(), 2, "{}", rpn);
+        assert_eq!(rpn.matches('&').count(), 3, "{}", rpn);
+        assert_eq!(TruthTable::from(tree), table);
+    }
+}
@@ -0,0 +1,371 @@
+//! Algebraic simplification of formulas by rewrite rules.
+//!
+//! A [`Simplifier`] rewrites a formula bottom-up: once the operands of a node are simplified,
+//! its rules are tried in order on the node, and whatever a rule returns is simplified again,
+//! until no rule applies anywhere. The default rules only ever shrink a formula or push a
+//! negation towards the variables, so they always reach that point.
+
+use crate::node::Node;
+use crate::operator::Operator;
+use crate::operator::Operator::*;
+use Node::*;
+
+/// A rewrite of a single node, whose operands are already simplified.
+///
+/// Closures `Fn(&Node) -> Option<Node>` are rules too. A rule must return `None` when it does
+/// not apply, and must not undo what another rule of the same simplifier does, lest the
+/// simplifier never stop.
+pub trait Rule {
+    fn apply(&self, node: &Node) -> Option<Node>;
+}
+
+impl<F: Fn(&Node) -> Option<Node>> Rule for F {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        self(node)
+    }
+}
+
+/// Ordered set of rules, applied until none matches.
+pub struct Simplifier {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Default for Simplifier {
+    /// Every rule of this module.
+    fn default() -> Self {
+        Self::empty()
+            .with_rule(ConstantFolding)
+            .with_rule(DoubleNegation)
+            .with_rule(Idempotence)
+            .with_rule(Complementation)
+            .with_rule(Absorption)
+            .with_rule(DeMorgan)
+    }
+}
+
+impl Simplifier {
+    /// Simplifier leaving formulas as they are, until rules are added.
+    pub fn empty() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Adds a rule, tried after the rules already there.
+    pub fn with_rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn simplify(&self, node: &Node) -> Node {
+        let node = match node {
+            UnaryExpr { op, child } => UnaryExpr {
+                op: *op,
+                child: Box::new(self.simplify(child)),
+            },
+            BinaryExpr { op, lhs, rhs } => BinaryExpr {
+                op: *op,
+                lhs: Box::new(self.simplify(lhs)),
+                rhs: Box::new(self.simplify(rhs)),
+            },
+            _ => node.clone(),
+        };
+        for rule in &self.rules {
+            if let Some(rewritten) = rule.apply(&node) {
+                return self.simplify(&rewritten);
+            }
+        }
+        node
+    }
+}
+
+/// Simplifies `node` with the default rules.
+pub fn simplify(node: &Node) -> Node {
+    Simplifier::default().simplify(node)
+}
+
+/// Removes constant operands: `A & 1` is `A`, `A | 1` is `1`, `A ^ 1` is `!A`, `!0` is `1`...
+pub struct ConstantFolding;
+
+impl Rule for ConstantFolding {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        match node {
+            UnaryExpr {
+                op,
+                child: box_child,
+            } => match **box_child {
+                Constant(x) => Some(Constant(op.eval_unary(x))),
+                _ => None,
+            },
+            BinaryExpr { op, lhs, rhs } => match (&**lhs, &**rhs) {
+                (Constant(a), Constant(b)) => Some(Constant(op.eval_binary(*a, *b))),
+                // `op` is symmetric but for `Imply`
+                (Constant(a), other) if *op != Imply => Some(with_constant(*op, *a, other)),
+                (other, Constant(b)) => Some(with_constant(*op, *b, other)),
+                (Constant(a), other) => Some(if *a { other.clone() } else { Constant(true) }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+// `other op constant`.
+fn with_constant(op: Operator, constant: bool, other: &Node) -> Node {
+    match (op, constant) {
+        (And, true) | (Or, false) | (Xor, false) | (Xnor, true) => other.clone(),
+        (And, false) => Constant(false),
+        (Or, true) | (Imply, true) => Constant(true),
+        (Xor, true) | (Xnor, false) | (Imply, false) => !other.clone(),
+        (Not, _) => unreachable!(),
+    }
+}
+
+/// `!!X` is `X`.
+pub struct DoubleNegation;
+
+impl Rule for DoubleNegation {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        match node {
+            UnaryExpr { child, .. } => match &**child {
+                UnaryExpr { child, .. } => Some((**child).clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Repeated operands: `X & X` and `X | X` are `X`, also inside longer chains such as repeated
+/// clauses, and `X ^ X` is `0` while `X = X` and `X > X` are `1`.
+pub struct Idempotence;
+
+impl Rule for Idempotence {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        match node {
+            BinaryExpr {
+                op: op @ (And | Or),
+                ..
+            } => {
+                let operands = operands(node, *op);
+                let mut distinct: Vec<&Node> = vec![];
+                for operand in &operands {
+                    if !distinct.contains(operand) {
+                        distinct.push(operand);
+                    }
+                }
+                (distinct.len() < operands.len()).then(|| chain(&distinct, *op))
+            }
+            BinaryExpr { op, lhs, rhs } if lhs == rhs => Some(Constant(*op != Xor)),
+            _ => None,
+        }
+    }
+}
+
+/// An operand next to its negation: `X & !X` is `0` and `X | !X` is `1`, also inside longer
+/// chains, `X ^ !X` is `1`, `X = !X` is `0`, and `X > !X` is `!X` while `!X > X` is `X`.
+pub struct Complementation;
+
+impl Rule for Complementation {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        match node {
+            BinaryExpr {
+                op: op @ (And | Or),
+                ..
+            } => {
+                let operands = operands(node, *op);
+                let complementary = operands
+                    .iter()
+                    .any(|a| operands.iter().any(|b| is_negation_of(a, b)));
+                complementary.then_some(Constant(*op == Or))
+            }
+            BinaryExpr { op, lhs, rhs } if is_negation_of(lhs, rhs) || is_negation_of(rhs, lhs) => {
+                Some(match op {
+                    Xor => Constant(true),
+                    Xnor => Constant(false),
+                    _ => (**rhs).clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn is_negation_of(node: &Node, other: &Node) -> bool {
+    matches!(other, UnaryExpr { child, .. } if **child == *node)
+}
+
+/// An operand containing another: `X & (X | Y)` is `X` and `X | (X & Y)` is `X`, also inside
+/// longer chains, so that a clause containing another clause goes away.
+pub struct Absorption;
+
+impl Rule for Absorption {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        let (op, dual) = match node {
+            BinaryExpr { op: And, .. } => (And, Or),
+            BinaryExpr { op: Or, .. } => (Or, And),
+            _ => return None,
+        };
+        let operands = operands(node, op);
+        let absorbed = |i: usize| {
+            let inner = self::operands(operands[i], dual);
+            inner.len() > 1
+                && operands
+                    .iter()
+                    .enumerate()
+                    .any(|(j, other)| j != i && inner.contains(other))
+        };
+        let kept: Vec<&Node> = (0..operands.len())
+            .filter(|&i| !absorbed(i))
+            .map(|i| operands[i])
+            .collect();
+        (kept.len() < operands.len()).then(|| chain(&kept, op))
+    }
+}
+
+/// Pushes negations down to the variables: `!(A & B)` is `!A | !B`, `!(A | B)` is `!A & !B`,
+/// `!(A ^ B)` is `A = B`, `!(A = B)` is `A ^ B`, and `!(A > B)` is `A & !B`.
+pub struct DeMorgan;
+
+impl Rule for DeMorgan {
+    fn apply(&self, node: &Node) -> Option<Node> {
+        let UnaryExpr { child, .. } = node else {
+            return None;
+        };
+        let BinaryExpr { op, lhs, rhs } = &**child else {
+            return None;
+        };
+        let (lhs, rhs) = ((**lhs).clone(), (**rhs).clone());
+        Some(match op {
+            And => !lhs | !rhs,
+            Or => !lhs & !rhs,
+            Xor => BinaryExpr {
+                op: Xnor,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            Xnor => lhs ^ rhs,
+            Imply => lhs & !rhs,
+            Not => unreachable!(),
+        })
+    }
+}
+
+// Operands of a chain of `op`, such as the clauses of a CNF.
+fn operands(node: &Node, op: Operator) -> Vec<&Node> {
+    match node {
+        BinaryExpr { op: o, lhs, rhs } if *o == op => {
+            let mut operands = self::operands(lhs, op);
+            operands.extend(self::operands(rhs, op));
+            operands
+        }
+        _ => vec![node],
+    }
+}
+
+// Right-nested chain of at least one operand.
+fn chain(operands: &[&Node], op: Operator) -> Node {
+    operands
+        .iter()
+        .rev()
+        .map(|&n| n.clone())
+        .reduce(|rhs, lhs| BinaryExpr {
+            op,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod simplify_test {
+    use crate::cnf::node_to_cnf;
+    use crate::node::Node;
+    use crate::node::Node::*;
+    use crate::operator::Operator::*;
+    use crate::simplify::{simplify, DoubleNegation, Simplifier};
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    fn simplified(formula: &str) -> String {
+        simplify(&Tree::from_str(formula).unwrap().root).to_rpn()
+    }
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(simplified("AA|"), "A");
+        assert_eq!(simplified("A1&"), "A");
+        assert_eq!(simplified("1A>"), "A");
+        assert_eq!(simplified("A0>"), "A!");
+        assert_eq!(simplified("0A>B&"), "B");
+        assert_eq!(simplified("A1^"), "A!");
+        assert_eq!(simplified("BB!&"), "0");
+        assert_eq!(simplified("B!B|"), "1");
+        assert_eq!(simplified("AA!>"), "A!");
+        assert_eq!(simplified("AA^"), "0");
+        assert_eq!(simplified("AA="), "1");
+        assert_eq!(simplified("X!!"), "X");
+        assert_eq!(simplified("AB|A&"), "A");
+        assert_eq!(simplified("AAB&|"), "A");
+        assert_eq!(simplified("AB&!"), "A!B!|");
+        assert_eq!(simplified("AB|C&!"), "A!B!&C!|");
+        assert_eq!(simplified("AB>!"), "AB!&");
+        assert_eq!(simplified("AB&CA&&"), "ABC&&");
+        assert_eq!(simplified("AB&C&A!&"), "0");
+        assert_eq!(simplified("1!0|"), "0");
+    }
+
+    #[test]
+    fn test_simplify_cnf_noise() {
+        // the distributed CNF of (A & B) | (A & C) repeats the clause A | A
+        let tree = Tree::from_str("AB&AC&|").unwrap();
+        let cnf = node_to_cnf(tree.root.clone());
+        assert_eq!(cnf.to_rpn(), "AA|AC|&BA|BC|&&");
+        assert_eq!(simplify(&cnf).to_rpn(), "ABC|&");
+    }
+
+    #[test]
+    fn test_simplify_matches_truth_table() {
+        let formulas = [
+            "AB&AC&|",
+            "AB|C&!D!!&",
+            "AB>BC>&AC!&|",
+            "AB^BC^&CA^&",
+            "AB=C1&0|^",
+            "ABC||D!&AB&!&",
+            "AA!&B|",
+            "AB&!AB!|&",
+        ];
+        for formula in formulas {
+            let tree = Tree::from_str(formula).unwrap();
+            assert_eq!(
+                TruthTable::from(tree.simplify()),
+                TruthTable::from(tree),
+                "{}",
+                formula
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // only the rules given apply
+        let simplifier = Simplifier::empty().with_rule(DoubleNegation);
+        let tree = Tree::from_str("A!!A|").unwrap();
+        assert_eq!(simplifier.simplify(&tree.root).to_rpn(), "AA|");
+
+        // a closure turning implications into disjunctions
+        let simplifier = Simplifier::default().with_rule(|node: &Node| match node {
+            BinaryExpr {
+                op: Imply,
+                lhs,
+                rhs,
+            } => Some(!(**lhs).clone() | (**rhs).clone()),
+            _ => None,
+        });
+        let tree = Tree::from_str("AB>C>").unwrap();
+        assert_eq!(simplifier.simplify(&tree.root).to_rpn(), "AB!&C|");
+        let mut simplified = tree.clone();
+        simplified.root = simplifier.simplify(&tree.root);
+        assert_eq!(TruthTable::from(simplified), TruthTable::from(tree));
+    }
+}
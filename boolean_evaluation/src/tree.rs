@@ -1,49 +1,303 @@
+use crate::allsat::Models;
+use crate::assignment::Assignment;
+use crate::bdd::{Bdd, BddRef, VariableOrder};
+use crate::big_uint::BigUint;
+use crate::cnf;
+use crate::cnf::{Cnf, Encoding};
+use crate::count::LiteralWeights;
+use crate::diff::Diff;
+use crate::error::ParseError;
+use crate::infix;
+use crate::infix::Token;
+use crate::literal::Clause;
+use crate::model::Model;
 use crate::node::Node;
 use crate::operator::Operator;
+use crate::truth_table::TruthTable;
 use crate::variable::Variable;
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::{bdd, cdcl, count, dimacs, dpll, espresso, quine_mccluskey, simplify};
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
+use std::sync::Arc;
 use Node::*;
 use Operator::*;
+use ParseError::*;
 
-pub type VariableRef = Rc<RefCell<Variable>>;
-pub type VariableRefList = Vec<Option<VariableRef>>;
-const VALID_TOKENS: &[char] = &['1', '0', '!', '&', '^', '=', '|', '>'];
+pub type VariableRef = Arc<Variable>;
+pub type VariableRefList = Vec<VariableRef>;
 
+/// Immutable formula with its variables, safe to share between threads.
+///
+/// Values and sets are never stored in the variables themselves: formulas are evaluated with
+/// [`Node::eval_with`] and sets live in the tree, so one `Tree` can serve any number of
+/// evaluations at once.
 #[derive(Debug, Clone)]
 pub struct Tree {
     pub root: Node,
     universe: Vec<i32>,
+    sets: Vec<Vec<i32>>,
     pub variable_list: Option<VariableRefList>,
 }
 
 impl Tree {
-    pub fn sat(self) -> bool {
-        if let Some(variable_list) = self.variable_list {
-            for bitfield in 0..2u32.pow(variable_list.len() as u32) {
-                for (i, v) in variable_list.iter().filter(|v| v.is_some()).enumerate() {
-                    {
-                        let mut v = v.as_ref().unwrap().borrow_mut();
-                        v.value = (bitfield & (1u32 << i)) != 0;
-                    }
-                    if self.root.eval_ref() {
-                        return true;
-                    }
-                }
-            }
-            false
-        } else {
-            false
+    /// Parses an infix formula such as `(A & B) | !C`, see [`crate::infix`].
+    pub fn from_infix(s: &str) -> Result<Self, ParseError> {
+        infix::parse(s)
+    }
+
+    pub(crate) fn from_parts(root: Node, variables: VariableTable) -> Self {
+        let mut list: VariableRefList = variables.0.into_values().collect();
+        list.sort_unstable_by_key(|v| v.index);
+        Self::from_variables(root, list)
+    }
+
+    /// Tree over `variables`, which must be indexed in order.
+    pub(crate) fn from_variables(root: Node, variables: VariableRefList) -> Self {
+        Self {
+            root,
+            universe: vec![],
+            sets: vec![],
+            variable_list: if variables.is_empty() {
+                None
+            } else {
+                Some(variables)
+            },
+        }
+    }
+
+    /// Tree over variables `names`, numbered and with the root built from their references in
+    /// that order.
+    pub(crate) fn from_names(names: &[String], root: impl FnOnce(&[VariableRef]) -> Node) -> Self {
+        let table = VariableTable::from_names(names.iter().map(String::as_str));
+        let variables: Vec<VariableRef> = names.iter().map(|n| table.variable(n)).collect();
+        Self::from_parts(root(&variables), table)
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variable_list.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Clauses of the formula, see [`cnf::encode`]. Auxiliary variables come after the
+    /// variables of the tree.
+    pub fn clauses(&self, encoding: Encoding) -> Vec<Clause> {
+        cnf::encode(&self.root, self.variable_count(), encoding)
+    }
+
+    /// Finds values for the variables making the formula true, with [`cdcl::solve`] on the
+    /// Tseitin clauses.
+    pub fn solve(&self) -> Option<Model> {
+        let count = self.variable_count();
+        let assignment = cdcl::solve(&self.clauses(Encoding::Tseitin), count)?;
+        let values = assignment.values()[..count].iter().copied().collect();
+        Some(Model::new(self.variable_names(), values))
+    }
+
+    /// `Ok` when the formula is true for every assignment, otherwise `Err` with one making it
+    /// false.
+    pub fn is_tautology(&self) -> Result<(), Model> {
+        let mut negation = self.clone();
+        negation.root = !self.root.clone();
+        negation.is_contradiction()
+    }
+
+    /// `Ok` when the formula is false for every assignment, otherwise `Err` with one making it
+    /// true.
+    pub fn is_contradiction(&self) -> Result<(), Model> {
+        self.solve().map_or(Ok(()), Err)
+    }
+
+    /// `Ok` when both formulas take the same value on every assignment, otherwise `Err` with one
+    /// on which they differ. Variables are matched by name, so the two trees need not have the
+    /// same variables, and the model gives a value to those of both.
+    pub fn equivalent(&self, other: &Tree) -> Result<(), Model> {
+        self.miter(other, |lhs, rhs| lhs ^ rhs).is_contradiction()
+    }
+
+    /// `Ok` when `other` is true wherever the formula is, otherwise `Err` with an assignment
+    /// making the formula true and `other` false. Variables are matched by name.
+    pub fn entails(&self, other: &Tree) -> Result<(), Model> {
+        self.miter(other, |lhs, rhs| lhs & !rhs).is_contradiction()
+    }
+
+    /// Assignments on which the formula and `other` differ, see [`Diff`].
+    pub fn diff(&self, other: &Tree) -> Diff {
+        Diff::new(self, other)
+    }
+
+    // Tree over the variables of both formulas, whose root combines the two roots.
+    pub(crate) fn miter(&self, other: &Tree, combine: impl FnOnce(Node, Node) -> Node) -> Tree {
+        let (variables, lhs, rhs) = self.merge(other);
+        Tree::from_variables(combine(lhs, rhs), variables)
+    }
+
+    // Variables of both formulas matched by name, in alphabetical order, and both roots over
+    // them.
+    pub(crate) fn merge(&self, other: &Tree) -> (VariableRefList, Node, Node) {
+        let mut names = self.variable_names();
+        names.extend(other.variable_names());
+        names.sort_unstable();
+        names.dedup();
+        let table = VariableTable::from_names(names.iter().map(String::as_str));
+        let rename = |v: &VariableRef| table.variable(&v.name);
+        (
+            names.iter().map(|name| table.variable(name)).collect(),
+            self.root.map_variables(&rename),
+            other.root.map_variables(&rename),
+        )
+    }
+
+    /// Number of satisfying assignments, see [`count::count_models`].
+    pub fn count_models(&self) -> BigUint {
+        count::count_models(&self.clauses(Encoding::Tseitin), self.variable_count())
+    }
+
+    /// Sum of the weights of the satisfying assignments, see [`count::weighted_count`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` does not hold one entry per variable.
+    pub fn weighted_count(&self, weights: &[LiteralWeights]) -> f64 {
+        if weights.len() != self.variable_count() {
+            panic!("Number of weights does not match number of variables");
         }
+        // each model extends to a single assignment of the auxiliary variables, weighing 1
+        let clauses = self.clauses(Encoding::Tseitin);
+        let auxiliary = clauses.iter().flatten().map(|l| l.variable + 1).max();
+        let mut weights = weights.to_vec();
+        weights.resize(
+            auxiliary.unwrap_or(0).max(weights.len()),
+            LiteralWeights {
+                positive: 1.0,
+                negative: 1.0,
+            },
+        );
+        count::weighted_count(&clauses, &weights)
+    }
+
+    /// Probability that the formula is true when each variable is true independently with the
+    /// probability given at its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probabilities` does not hold one entry per variable.
+    pub fn probability(&self, probabilities: &[f64]) -> f64 {
+        let weights: Vec<LiteralWeights> = probabilities
+            .iter()
+            .map(|&p| LiteralWeights::probability(p))
+            .collect();
+        self.weighted_count(&weights)
+    }
+
+    /// The clauses of [`node_to_cnf`](cnf::node_to_cnf) in DIMACS format, see [`dimacs::write`].
+    pub fn to_dimacs(&self) -> String {
+        dimacs::write(&Cnf::from(&self.root).clauses, &self.variable_names())
+    }
+
+    /// Diagram of the formula over its variables, ordered as `order` says.
+    pub fn to_bdd(&self, order: VariableOrder) -> (Bdd, BddRef) {
+        let count = self.variable_count();
+        let mut bdd = match order {
+            VariableOrder::Index => Bdd::new(count),
+            _ => Bdd::with_order(bdd::dfs_order(&self.root, count)),
+        };
+        let f = bdd.compile(&self.root);
+        if order == VariableOrder::Sifted {
+            bdd.sift(&[f]);
+        }
+        (bdd, f)
+    }
+
+    /// Smallest sum of products equal to the formula, see [`quine_mccluskey::minimize`].
+    pub fn minimize(&self) -> Tree {
+        if self.variable_list.is_none() {
+            let mut tree = self.clone();
+            tree.root = Constant(self.root.eval_with(&Assignment::default()));
+            return tree;
+        }
+        quine_mccluskey::minimize(&TruthTable::from(self.clone()))
+    }
+
+    /// Small sum of products equal to the formula wherever `dont_care` is false, see
+    /// [`espresso::minimize`]. Variables are matched by name, those of `dont_care` only making
+    /// a row a don't care when `dont_care` is true for some value of them.
+    pub fn espresso(&self, dont_care: Option<&Tree>) -> Tree {
+        let variables = self.variable_list.clone().unwrap_or_default();
+        let dont_care = dont_care.map_or(Constant(false), |tree| {
+            // the variables of the formula first, keeping their indices
+            let mut names = self.variable_names();
+            names.extend(tree.variable_names());
+            let table = VariableTable::from_names(names.iter().map(String::as_str));
+            tree.root.map_variables(&|v| table.variable(&v.name))
+        });
+        let mut tree = self.clone();
+        tree.root = espresso::minimize(&self.root, &dont_care, &variables);
+        tree
+    }
+
+    /// Equivalent formula rewritten by the default rules of [`simplify`], over the same
+    /// variables even when some of them no longer occur.
+    pub fn simplify(&self) -> Tree {
+        let mut tree = self.clone();
+        tree.root = simplify::simplify(&self.root);
+        tree
+    }
+
+    /// Lazily enumerates every satisfying assignment of the formula.
+    pub fn models(&self) -> Models {
+        let names = self.variable_names();
+        self.models_over(names, (0..self.variable_count()).collect())
+    }
+
+    /// Lazily enumerates the distinct values of variables `names` over the satisfying
+    /// assignments, in the order given.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the formula has no variable of one of the names.
+    pub fn projected_models(&self, names: &[&str]) -> Models {
+        let variables = self.variable_names();
+        let projection = names
+            .iter()
+            .map(|name| {
+                variables
+                    .iter()
+                    .position(|v| v == name)
+                    .unwrap_or_else(|| panic!("No variable named {}", name))
+            })
+            .collect();
+        self.models_over(names.iter().map(|n| n.to_string()).collect(), projection)
+    }
+
+    fn models_over(&self, names: Vec<String>, projection: Vec<usize>) -> Models {
+        Models::new(
+            names,
+            projection,
+            self.clauses(Encoding::Tseitin),
+            self.variable_count(),
+        )
+    }
+
+    pub fn variable_names(&self) -> Vec<String> {
+        self.variable_list
+            .iter()
+            .flatten()
+            .map(|v| v.name.clone())
+            .collect()
+    }
+
+    /// Decides satisfiability with [`dpll::solve`] on the Plaisted–Greenbaum clauses.
+    pub fn sat(self) -> bool {
+        let clauses = self.clauses(Encoding::PlaistedGreenbaum);
+        dpll::solve(&clauses, self.variable_count()).is_some()
     }
 
     pub fn evaluate_sets(self) -> Vec<i32> {
-        let ret = self.root.compute_sets();
+        let ret = self.root.compute_sets(&self.sets);
         if ret.is_complement {
             self.universe
                 .iter()
-                .filter(|x| !ret.values.contains(x)).copied()
+                .filter(|x| !ret.values.contains(x))
+                .copied()
                 .collect()
         } else {
             ret.values
@@ -58,95 +312,194 @@ impl Tree {
             self.universe = sets.iter().flatten().copied().collect();
             self.universe.sort_unstable();
             self.universe.dedup();
-            sets.into_iter()
-                .zip(variable_list.iter())
-                .filter(|(_, v)| v.is_some())
-                .for_each(|(set, v)| {
-                    let mut v = v.as_ref().unwrap().borrow_mut();
-                    v.set = Some(set);
-                });
+            self.sets = sets;
+        }
+    }
+}
+
+/// Variables of a formula being parsed, numbered in alphabetical order of their names.
+pub(crate) struct VariableTable<'a>(BTreeMap<&'a str, VariableRef>);
+
+impl<'a> VariableTable<'a> {
+    /// Variables of the tokens, numbered in alphabetical order.
+    pub(crate) fn new(tokens: &[(usize, Token<'a>)]) -> Self {
+        let names: BTreeSet<&str> = tokens
+            .iter()
+            .filter_map(|(_, token)| match token {
+                Token::Variable(name) => Some(*name),
+                _ => None,
+            })
+            .collect();
+        Self::from_names(names)
+    }
+
+    /// Variables `names`, numbered in order of first appearance.
+    pub(crate) fn from_names(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut table = BTreeMap::new();
+        for name in names {
+            let index = table.len();
+            table
+                .entry(name)
+                .or_insert_with(|| Arc::new(Variable::new(name, index)));
         }
+        Self(table)
+    }
+
+    /// Returns variable `name`, which must be in the table.
+    pub(crate) fn variable(&self, name: &str) -> VariableRef {
+        self.0[name].clone()
+    }
+
+    /// Returns the node for variable `name`, which must appear in the tokens.
+    pub(crate) fn node(&self, name: &str) -> Node {
+        Variable(self.variable(name))
     }
 }
 
 impl FromStr for Tree {
-    type Err = String;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() && !s.contains(VALID_TOKENS) {
-            return Err(String::from("Empty input!"));
+        if s.is_empty() {
+            return Err(EmptyInput);
         }
 
+        let tokens = tokenize(s)?;
+        let variables = VariableTable::new(&tokens);
         let mut node_stack: Vec<Node> = Vec::with_capacity(50);
-        let mut vec_variables: VariableRefList = vec![None; 26];
-
-        for c in s.chars() {
-            let node = match c {
-                'A'..='Z' => {
-                    let idx = c as usize - 'A' as usize;
-                    if let Some(v) = &vec_variables[idx] {
-                        Variable(v.clone())
+
+        for (position, token) in tokens {
+            let node = match token {
+                Token::Variable(name) => variables.node(name),
+                Token::Constant(x) => Constant(x),
+                Token::Operator(op) => {
+                    let arity = if op == Not { 1 } else { 2 };
+                    if node_stack.len() < arity {
+                        return Err(MissingOperands {
+                            position,
+                            operator: op,
+                        });
+                    }
+                    let rhs = Box::new(node_stack.pop().unwrap());
+                    if op == Not {
+                        UnaryExpr { op, child: rhs }
                     } else {
-                        let v = Rc::new(RefCell::new(Variable::new(c)));
-                        vec_variables[idx] = Some(v.clone());
-                        Variable(v)
+                        let lhs = Box::new(node_stack.pop().unwrap());
+                        BinaryExpr { op, lhs, rhs }
                     }
                 }
-                '1' => Constant(true),
-                '0' => Constant(false),
-                '&' => BinaryExpr {
-                    op: And,
-                    rhs: Box::new(node_stack.pop().expect("Invalid input")),
-                    lhs: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                '|' => BinaryExpr {
-                    op: Or,
-                    rhs: Box::new(node_stack.pop().expect("Invalid input")),
-                    lhs: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                '^' => BinaryExpr {
-                    op: Xor,
-                    rhs: Box::new(node_stack.pop().expect("Invalid input")),
-                    lhs: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                '>' => BinaryExpr {
-                    op: Imply,
-                    rhs: Box::new(node_stack.pop().expect("Invalid input")),
-                    lhs: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                '=' => BinaryExpr {
-                    op: Xnor,
-                    rhs: Box::new(node_stack.pop().expect("Invalid input")),
-                    lhs: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                '!' => UnaryExpr {
-                    op: Not,
-                    child: Box::new(node_stack.pop().expect("Invalid input")),
-                },
-                _ => return Err("Invalid input".to_string()),
+                Token::OpenParen | Token::CloseParen => unreachable!(),
             };
             node_stack.push(node);
         }
         if node_stack.len() != 1 {
-            Err("Invalid input".to_string())
-        } else {
-            Ok(Self {
-                root: node_stack.remove(0),
-                universe: vec![],
-                variable_list: if s.contains(char::is_alphabetic) {
-                    vec_variables.retain(|v| v.is_some());
-                    Some(vec_variables)
-                } else {
-                    None
-                },
+            Err(LeftoverOperands {
+                count: node_stack.len(),
             })
+        } else {
+            Ok(Self::from_parts(node_stack.remove(0), variables))
         }
     }
 }
 
+// Single letters are variables on their own, longer names go between brackets: `[feature_x]`.
+fn tokenize(s: &str) -> Result<Vec<(usize, Token<'_>)>, ParseError> {
+    let mut tokens = Vec::with_capacity(s.len());
+    let mut chars = s.char_indices();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            'A'..='Z' => Token::Variable(&s[position..position + 1]),
+            '[' => {
+                let end = s[position..]
+                    .find(']')
+                    .ok_or(UnclosedBracket { position })?;
+                let name = &s[position + 1..position + end];
+                if !Variable::is_valid_name(name) {
+                    return Err(InvalidName { position });
+                }
+                chars.nth(name.chars().count());
+                Token::Variable(name)
+            }
+            '1' => Token::Constant(true),
+            '0' => Token::Constant(false),
+            _ => Token::Operator(
+                Operator::try_from(c).map_err(|_| UnknownToken { position, token: c })?,
+            ),
+        };
+        tokens.push((position, token));
+    }
+    Ok(tokens)
+}
+
 #[cfg(test)]
 mod tree_tests {
 
+    mod parse_tests {
+        use crate::error::ParseError::*;
+        use crate::operator::Operator::*;
+        use crate::tree::Tree;
+        use std::str::FromStr;
+
+        #[test]
+        fn test_parse_errors() {
+            assert_eq!(Tree::from_str("").unwrap_err(), EmptyInput);
+            assert_eq!(
+                Tree::from_str("AB&&").unwrap_err(),
+                MissingOperands {
+                    position: 3,
+                    operator: And
+                }
+            );
+            assert_eq!(
+                Tree::from_str("!").unwrap_err(),
+                MissingOperands {
+                    position: 0,
+                    operator: Not
+                }
+            );
+            assert_eq!(
+                Tree::from_str("A@").unwrap_err(),
+                UnknownToken {
+                    position: 1,
+                    token: '@'
+                }
+            );
+            assert_eq!(
+                Tree::from_str("ABC&").unwrap_err(),
+                LeftoverOperands { count: 2 }
+            );
+        }
+
+        #[test]
+        fn test_parse_names() {
+            let tree = Tree::from_str("[feature_x][net_42]&A|").unwrap();
+            let names: Vec<String> = tree
+                .variable_list
+                .unwrap()
+                .iter()
+                .map(|v| v.name.clone())
+                .collect();
+            assert_eq!(names, vec!["A", "feature_x", "net_42"]);
+
+            let tree = Tree::from_str("[A]A&").unwrap();
+            assert_eq!(tree.variable_list.unwrap().len(), 1);
+
+            assert_eq!(
+                Tree::from_str("[feature_x").unwrap_err(),
+                UnclosedBracket { position: 0 }
+            );
+            assert_eq!(
+                Tree::from_str("A[]&").unwrap_err(),
+                InvalidName { position: 1 }
+            );
+            assert_eq!(
+                Tree::from_str("A[4x]&").unwrap_err(),
+                InvalidName { position: 1 }
+            );
+        }
+    }
+
     mod sat_tests {
         use crate::tree::Tree;
         use std::str::FromStr;
@@ -168,7 +521,138 @@ mod tree_tests {
             assert!(tree.sat());
             let tree = Tree::from_str("AB|").unwrap();
             assert!(tree.sat());
+            let tree = Tree::from_str("1").unwrap();
+            assert!(tree.sat());
+        }
 
+        #[test]
+        fn test_solve() {
+            let tree = Tree::from_str("AB^[c]&A>").unwrap();
+            let model = tree.solve().unwrap();
+            assert!(tree.root.eval_with(model.assignment()));
+            assert_eq!(model.variables(), &["A", "B", "c"]);
+            assert_eq!(model.get("D"), None);
+
+            let tree = Tree::from_str("AB|A!&B!&").unwrap();
+            assert_eq!(tree.solve(), None);
+        }
+
+        #[test]
+        fn test_model_display() {
+            let tree = Tree::from_str("A![net_42]&").unwrap();
+            let model = tree.solve().unwrap();
+            assert_eq!(model.get("A"), Some(false));
+            assert_eq!(model.get("net_42"), Some(true));
+            assert_eq!(
+                model.to_string(),
+                "| A | net_42 |\n\
+                 |---|--------|\n\
+                 | 0 | 1      |\n"
+            );
+        }
+
+        #[test]
+        fn test_sat_many_variables() {
+            // 40 variables chained by implications, out of reach of truth table enumeration
+            let names: Vec<String> = (0..40).map(|i| format!("[x{}]", i)).collect();
+            let mut formula = names[0].clone();
+            for pair in names.windows(2) {
+                formula.push_str(&format!("{}{}>&", pair[0], pair[1]));
+            }
+            let tree = Tree::from_str(&format!("{}{}!&", formula, names[39])).unwrap();
+            assert!(!tree.sat());
+            let tree = Tree::from_str(&format!("{}{}&", formula, names[39])).unwrap();
+            assert!(tree.sat());
+        }
+    }
+
+    mod check_tests {
+        use crate::assignment::Assignment;
+        use crate::model::Model;
+        use crate::tree::Tree;
+        use std::str::FromStr;
+
+        fn eval(tree: &Tree, model: &Model) -> bool {
+            let assignment: Assignment = tree
+                .variable_names()
+                .iter()
+                .map(|name| model.get(name).unwrap())
+                .collect();
+            tree.root.eval_with(&assignment)
+        }
+
+        #[test]
+        fn test_equivalent() {
+            let tree = |formula: &str| Tree::from_str(formula).unwrap();
+            assert_eq!(tree("AB&!").equivalent(&tree("A!B!|")), Ok(()));
+            assert_eq!(tree("AB>").equivalent(&tree("B!A!>")), Ok(()));
+            // `B` only occurs on one side, where it makes no difference
+            assert_eq!(tree("A").equivalent(&tree("AB&AB!&|")), Ok(()));
+            assert_eq!(tree("1").equivalent(&tree("AA!|")), Ok(()));
+
+            let (lhs, rhs) = (tree("AB|C&"), tree("AB&C|"));
+            let model = lhs.equivalent(&rhs).unwrap_err();
+            assert_eq!(model.variables(), &["A", "B", "C"]);
+            assert_ne!(eval(&lhs, &model), eval(&rhs, &model));
+
+            // the variables of each side take the values of the same name
+            let (lhs, rhs) = (tree("[x]"), tree("[y]!"));
+            let model = lhs.equivalent(&rhs).unwrap_err();
+            assert_eq!(model.variables(), &["x", "y"]);
+            assert_ne!(eval(&lhs, &model), eval(&rhs, &model));
+        }
+
+        #[test]
+        fn test_entails() {
+            let tree = |formula: &str| Tree::from_str(formula).unwrap();
+            assert_eq!(tree("AB&").entails(&tree("A")), Ok(()));
+            assert_eq!(tree("AB>A&").entails(&tree("B")), Ok(()));
+            assert_eq!(tree("0").entails(&tree("C")), Ok(()));
+            let (lhs, rhs) = (tree("AB|"), tree("A"));
+            let model = lhs.entails(&rhs).unwrap_err();
+            assert_eq!(model.get("A"), Some(false));
+            assert_eq!(model.get("B"), Some(true));
+        }
+
+        #[test]
+        fn test_tautology_and_contradiction() {
+            let tree = |formula: &str| Tree::from_str(formula).unwrap();
+            assert_eq!(tree("AB>A&B>").is_tautology(), Ok(()));
+            assert_eq!(tree("1").is_tautology(), Ok(()));
+            assert_eq!(tree("AA!&").is_contradiction(), Ok(()));
+            let formula = tree("AB>");
+            let model = formula.is_tautology().unwrap_err();
+            assert!(!eval(&formula, &model));
+            let model = formula.is_contradiction().unwrap_err();
+            assert!(eval(&formula, &model));
+        }
+
+        #[test]
+        fn test_equivalent_many_variables() {
+            // `x0 & (x0 > x1) & ... & (x38 > x39)` against the conjunction of all 40 variables
+            let names: Vec<String> = (0..40).map(|i| format!("[x{}]", i)).collect();
+            let mut chain = names[0].clone();
+            for pair in names.windows(2) {
+                chain.push_str(&format!("{}{}>&", pair[0], pair[1]));
+            }
+            let conjunction = names[1..]
+                .iter()
+                .fold(names[0].clone(), |f, name| format!("{}{}&", f, name));
+            let chain = Tree::from_str(&chain).unwrap();
+            let conjunction = Tree::from_str(&conjunction).unwrap();
+            assert_eq!(chain.equivalent(&conjunction), Ok(()));
+
+            // dropping one link lets the variables past it be false
+            let mut broken = names[0].clone();
+            for pair in names.windows(2).filter(|pair| pair[0] != "[x20]") {
+                broken.push_str(&format!("{}{}>&", pair[0], pair[1]));
+            }
+            let broken = Tree::from_str(&broken).unwrap();
+            assert_eq!(conjunction.entails(&broken), Ok(()));
+            let model = broken.equivalent(&conjunction).unwrap_err();
+            assert_eq!(model.get("x0"), Some(true));
+            assert!(eval(&broken, &model));
+            assert!(!eval(&conjunction, &model));
         }
     }
 
@@ -192,4 +676,36 @@ mod tree_tests {
             assert_eq!(tree.evaluate_sets(), vec![0, 1, 2, 3]);
         }
     }
+
+    mod thread_tests {
+        use crate::assignment::Assignment;
+        use crate::tree::Tree;
+        use std::str::FromStr;
+        use std::thread;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        #[test]
+        fn test_tree_is_send_sync() {
+            assert_send_sync::<Tree>();
+        }
+
+        #[test]
+        fn test_shared_evaluation() {
+            let tree = Tree::from_str("AB^C|").unwrap();
+            let results: Vec<bool> = thread::scope(|s| {
+                let handles: Vec<_> = (0..8u64)
+                    .map(|bitfield| {
+                        let tree = &tree;
+                        s.spawn(move || tree.root.eval_with(&Assignment::from_bits(bitfield, 3)))
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+            assert_eq!(
+                results,
+                vec![false, true, true, false, true, true, true, true]
+            );
+        }
+    }
 }
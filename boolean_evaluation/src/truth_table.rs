@@ -1,59 +1,167 @@
+use crate::assignment::Assignment;
+use crate::literal::{terms_to_node, Literal};
+use crate::operator::Operator::{And, Or};
 use crate::tree::Tree;
+use crate::variable::Variable;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TruthTable {
-    variables: Vec<char>,
+    variables: Vec<String>,
     values: Vec<Vec<bool>>,
 }
 
+impl TruthTable {
+    /// Table of a function given by its value on each row, in the order of [`Display`]: row `i`
+    /// gives variable `j` the value of bit `j` of `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics on an invalid or repeated name, with 64 names or more, or unless there are `2^n`
+    /// values for `n` names.
+    pub fn new(variables: Vec<String>, outputs: &[bool]) -> Self {
+        for (i, name) in variables.iter().enumerate() {
+            if !Variable::is_valid_name(name) || variables[..i].contains(name) {
+                panic!("Invalid or repeated variable name {}", name);
+            }
+        }
+        assert!(
+            variables.len() < 64,
+            "At most 63 variables fit in a truth table"
+        );
+        if outputs.len() as u64 != 1 << variables.len() {
+            panic!("Number of values does not match number of rows");
+        }
+        let values = outputs
+            .iter()
+            .enumerate()
+            .map(|(bitfield, &output)| {
+                let mut row = Assignment::from_bits(bitfield as u64, variables.len())
+                    .values()
+                    .to_vec();
+                row.push(output);
+                row
+            })
+            .collect();
+        Self { variables, values }
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Value of the function on each row, in order.
+    pub fn outputs(&self) -> impl Iterator<Item = bool> + '_ {
+        self.values.iter().map(|row| row[row.len() - 1])
+    }
+
+    /// Disjunction of one conjunction per true row, equal to the table. The result is `0` when
+    /// no row is true. Variables keep the order of the columns.
+    pub fn to_minterm_form(&self) -> Tree {
+        let minterms: Vec<Vec<Literal>> = self
+            .rows(true)
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(v, &value)| Literal {
+                        variable: v,
+                        negated: !value,
+                    })
+                    .collect()
+            })
+            .collect();
+        Tree::from_names(&self.variables, |variables| {
+            terms_to_node(&minterms, variables, And, Or)
+        })
+    }
+
+    /// Conjunction of one disjunction per false row, equal to the table. The result is `1` when
+    /// no row is false.
+    pub fn to_maxterm_form(&self) -> Tree {
+        let maxterms: Vec<Vec<Literal>> = self
+            .rows(false)
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .map(|(v, &value)| Literal {
+                        variable: v,
+                        negated: value,
+                    })
+                    .collect()
+            })
+            .collect();
+        Tree::from_names(&self.variables, |variables| {
+            terms_to_node(&maxterms, variables, Or, And)
+        })
+    }
+
+    // Variable values of the rows where the function is `output`.
+    fn rows(&self, output: bool) -> impl Iterator<Item = &[bool]> {
+        self.values
+            .iter()
+            .filter(move |row| row[row.len() - 1] == output)
+            .map(|row| &row[..row.len() - 1])
+    }
+}
+
 impl Display for TruthTable {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut header = String::with_capacity(self.variables.len() * 3 + 4);
-        self.variables.iter().for_each(|var| {
-            header.push_str(format!("| {} ", var).as_str());
-        });
-        header.push_str("| = |");
-        let separator = header.replace(|ch| ch != '|', "-");
-        let mut body = String::with_capacity(self.variables.len() * self.variables.len() * 3 + 3);
-        self.values.iter().for_each(|row| {
-            for val in row.iter() {
-                body.push_str(format!("| {} ", *val as u8).as_str());
-            }
-            body.push_str("|\n");
-        });
-        write!(f, "{}\n{}\n{}", header, separator, body)
+        let mut columns: Vec<&str> = self.variables.iter().map(String::as_str).collect();
+        columns.push("=");
+        write!(f, "{}", format_table(&columns, &self.values))
     }
 }
 
+/// Lays out rows of booleans under the given column names, in the style of [`TruthTable`].
+pub(crate) fn format_table(columns: &[&str], rows: &[Vec<bool>]) -> String {
+    let rows: Vec<Vec<char>> = rows
+        .iter()
+        .map(|row| row.iter().map(|&val| if val { '1' } else { '0' }).collect())
+        .collect();
+    format_cells(columns, &rows)
+}
+
+/// [`format_table`] for rows of any one-character cells, such as `-` for a free variable.
+pub(crate) fn format_cells(columns: &[&str], rows: &[Vec<char>]) -> String {
+    let mut header = String::with_capacity(columns.len() * 4 + 1);
+    columns.iter().for_each(|column| {
+        header.push_str(format!("| {} ", column).as_str());
+    });
+    header.push('|');
+    let separator = header.replace(|ch| ch != '|', "-");
+    let mut body = String::with_capacity(rows.len() * (columns.len() * 4 + 2));
+    rows.iter().for_each(|row| {
+        for (val, column) in row.iter().zip(columns) {
+            body.push_str(format!("| {:<width$} ", val, width = column.len()).as_str());
+        }
+        body.push_str("|\n");
+    });
+    format!("{}\n{}\n{}", header, separator, body)
+}
+
 impl From<Tree> for TruthTable {
+    /// # Panics
+    ///
+    /// Panics without variables, or with 64 or more since the rows could not be counted.
     fn from(tree: Tree) -> Self {
         if tree.variable_list.is_none() {
             panic!("Tree must have a variable list");
         }
         if let Some(variable_list) = tree.variable_list.as_ref() {
-            let values: Vec<Vec<bool>> = (0..2u32.pow(variable_list.len() as u32))
+            assert!(
+                variable_list.len() < 64,
+                "At most 63 variables fit in a truth table"
+            );
+            let values: Vec<Vec<bool>> = (0..2u64.pow(variable_list.len() as u32))
                 .map(|bitfield| {
-                    let mut tmp = variable_list
-                        .iter()
-                        .enumerate()
-                        .map(|(i, v)| {
-                            let mask = (bitfield & (1u32 << i)) != 0;
-                            if let Some(v) = v {
-                                v.borrow_mut().value = mask;
-                            }
-                            mask
-                        })
-                        .collect::<Vec<bool>>();
-                    tmp.push(tree.clone().root.eval_ref());
+                    let assignment = Assignment::from_bits(bitfield, variable_list.len());
+                    let mut tmp = assignment.values().to_vec();
+                    tmp.push(tree.root.eval_with(&assignment));
                     tmp
                 })
                 .collect();
             Self {
-                variables: variable_list
-                    .iter()
-                    .map(|v| v.as_ref().unwrap().borrow().name)
-                    .collect(),
+                variables: variable_list.iter().map(|v| v.name.clone()).collect(),
                 values,
             }
         } else {
@@ -61,3 +169,96 @@ impl From<Tree> for TruthTable {
         }
     }
 }
+
+#[cfg(test)]
+mod truth_table_test {
+    use crate::assignment::Assignment;
+    use crate::tree::Tree;
+    use crate::truth_table::TruthTable;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_canonical_forms() {
+        let table = TruthTable::from(Tree::from_str("AB^").unwrap());
+        assert_eq!(table.to_minterm_form().root.to_rpn(), "AB!&A!B&|");
+        assert_eq!(table.to_maxterm_form().root.to_rpn(), "AB|A!B!|&");
+
+        for formula in ["AB&C|", "AB>C=", "AB|C&D!^", "[x][y]>!", "AA!&", "AA!|"] {
+            let table = TruthTable::from(Tree::from_str(formula).unwrap());
+            let minterms = table.to_minterm_form();
+            let maxterms = table.to_maxterm_form();
+            assert_eq!(minterms.variable_names(), table.variables, "{}", formula);
+            assert_eq!(maxterms.variable_names(), table.variables, "{}", formula);
+            let count = table.variables.len();
+            for (bitfield, row) in table.values.iter().enumerate() {
+                let assignment = Assignment::from_bits(bitfield as u64, count);
+                assert_eq!(
+                    minterms.root.eval_with(&assignment),
+                    row[count],
+                    "{}",
+                    formula
+                );
+                assert_eq!(
+                    maxterms.root.eval_with(&assignment),
+                    row[count],
+                    "{}",
+                    formula
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_outputs() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let table = TruthTable::new(names, &[false, true, true, true]);
+        assert_eq!(table, TruthTable::from(Tree::from_str("[a][b]|").unwrap()));
+        let tree = table.to_minterm_form();
+        assert_eq!(tree.root.to_rpn(), "[a][b]!&[a]![b]&[a][b]&||");
+        assert_eq!(TruthTable::from(tree), table);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 63 variables")]
+    fn test_from_outputs_too_many_variables() {
+        let names = (0..64).map(|i| format!("x{}", i)).collect();
+        TruthTable::new(names, &[true]);
+    }
+
+    #[test]
+    fn test_forms_keep_variable_order() {
+        // `B & !A`, with `B` as the first column
+        let names = vec!["B".to_string(), "A".to_string()];
+        let table = TruthTable::new(names.clone(), &[false, true, false, false]);
+        for tree in [table.to_minterm_form(), table.to_maxterm_form()] {
+            assert_eq!(tree.variable_names(), names);
+            assert_eq!(TruthTable::from(tree), table);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_outputs_length() {
+        TruthTable::new(vec!["A".to_string()], &[true]);
+    }
+
+    #[test]
+    #[should_panic(expected = "At most 63 variables")]
+    fn test_from_too_many_variables() {
+        let mut formula: String = (0..64).map(|i| format!("[x{}]", i)).collect();
+        formula.push_str(&"|".repeat(63));
+        let _ = TruthTable::from(Tree::from_str(&formula).unwrap());
+    }
+
+    #[test]
+    fn test_display_names() {
+        let example = "| A | net_42 | = |\n\
+            |---|--------|---|\n\
+            | 0 | 0      | 0 |\n\
+            | 1 | 0      | 0 |\n\
+            | 0 | 1      | 0 |\n\
+            | 1 | 1      | 1 |\n";
+        let output = TruthTable::from(Tree::from_str("A[net_42]&").unwrap());
+        assert_eq!(example, output.to_string());
+    }
+}
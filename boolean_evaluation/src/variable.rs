@@ -1,16 +1,23 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A named variable, numbered by its position in the tree's variable list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Variable {
-    pub name: char,
-    pub value: bool,
-    pub set: Option<Vec<i32>>,
+    pub name: String,
+    /// Position in the tree's variable list, used to look the variable up in an assignment.
+    pub index: usize,
 }
 
 impl Variable {
-    pub fn new(name: char) -> Self {
+    pub fn new(name: &str, index: usize) -> Self {
         Self {
-            name,
-            value: false,
-            set: None,
+            name: name.to_string(),
+            index,
         }
     }
+
+    /// Names start with a letter or `_`, followed by letters, digits or `_`.
+    pub fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
 }
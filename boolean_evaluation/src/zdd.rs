@@ -0,0 +1,466 @@
+//! Zero-suppressed decision diagrams of families of sets of integers.
+//!
+//! A [`Zdd`] holds any number of families. Each node tests whether its element belongs to a
+//! set, elements being tested in increasing order, and a node whose sets with the element form
+//! the empty family is left out. Families of sets drawn from a large universe, such as the
+//! power set of a hundred elements, thus take room in proportion to their structure rather
+//! than to their number of sets. Nodes are hash-consed, so equal families have equal
+//! [`ZddRef`]s.
+
+use crate::big_uint::BigUint;
+use std::collections::HashMap;
+use Operation::*;
+
+/// Family held by a [`Zdd`], valid for that manager only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ZddRef(usize);
+
+impl ZddRef {
+    /// The family without any set.
+    pub const EMPTY: ZddRef = ZddRef(0);
+    /// The family of the empty set alone.
+    pub const BASE: ZddRef = ZddRef(1);
+
+    pub fn is_constant(self) -> bool {
+        self.0 < 2
+    }
+}
+
+/// Sets of `low`, and sets of `high` with `element` added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ZddNode {
+    element: i32,
+    low: ZddRef,
+    high: ZddRef,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+    Join,
+    Subsets,
+    Supersets,
+}
+
+/// Manager owning the nodes of families of sets.
+#[derive(Debug, Clone)]
+pub struct Zdd {
+    nodes: Vec<ZddNode>,
+    unique: HashMap<(i32, ZddRef, ZddRef), ZddRef>,
+    computed: HashMap<(Operation, ZddRef, ZddRef), ZddRef>,
+}
+
+impl Default for Zdd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Zdd {
+    pub fn new() -> Self {
+        let terminal = ZddNode {
+            element: 0,
+            low: ZddRef::EMPTY,
+            high: ZddRef::EMPTY,
+        };
+        Self {
+            nodes: vec![terminal, terminal],
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+        }
+    }
+
+    // The one node for `element` between `low` and `high`, or `low` when `high` is empty.
+    fn make(&mut self, element: i32, low: ZddRef, high: ZddRef) -> ZddRef {
+        if high == ZddRef::EMPTY {
+            return low;
+        }
+        if let Some(&f) = self.unique.get(&(element, low, high)) {
+            return f;
+        }
+        let f = ZddRef(self.nodes.len());
+        self.nodes.push(ZddNode { element, low, high });
+        self.unique.insert((element, low, high), f);
+        f
+    }
+
+    // Element tested by `f`, `None` for constants.
+    fn top(&self, f: ZddRef) -> Option<i32> {
+        (!f.is_constant()).then(|| self.nodes[f.0].element)
+    }
+
+    // Sets of `f` without `element` and sets with it, `element` removed, for an `element`
+    // at most the one `f` tests.
+    fn cofactors(&self, f: ZddRef, element: i32) -> (ZddRef, ZddRef) {
+        if self.top(f) == Some(element) {
+            (self.nodes[f.0].low, self.nodes[f.0].high)
+        } else {
+            (f, ZddRef::EMPTY)
+        }
+    }
+
+    /// The family of `set` alone. Repeated elements count once.
+    pub fn singleton(&mut self, set: &[i32]) -> ZddRef {
+        let mut set = set.to_vec();
+        set.sort_unstable();
+        set.dedup();
+        set.iter().rev().fold(ZddRef::BASE, |f, &element| {
+            self.make(element, ZddRef::EMPTY, f)
+        })
+    }
+
+    /// The family of `sets`.
+    pub fn family(&mut self, sets: &[Vec<i32>]) -> ZddRef {
+        sets.iter().fold(ZddRef::EMPTY, |f, set| {
+            let g = self.singleton(set);
+            self.union(f, g)
+        })
+    }
+
+    /// Every subset of `set`, one node per element.
+    pub fn powerset(&mut self, set: &[i32]) -> ZddRef {
+        let mut set = set.to_vec();
+        set.sort_unstable();
+        set.dedup();
+        set.iter()
+            .rev()
+            .fold(ZddRef::BASE, |f, &element| self.make(element, f, f))
+    }
+
+    pub fn union(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Union, f, g)
+    }
+
+    pub fn intersection(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Intersection, f, g)
+    }
+
+    /// Sets of `f` not in `g`.
+    pub fn difference(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Difference, f, g)
+    }
+
+    /// Unions of a set of `f` and a set of `g`.
+    pub fn join(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Join, f, g)
+    }
+
+    /// Sets of `f` contained in some set of `g`.
+    pub fn subsets(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Subsets, f, g)
+    }
+
+    /// Sets of `f` containing some set of `g`.
+    pub fn supersets(&mut self, f: ZddRef, g: ZddRef) -> ZddRef {
+        self.apply(Supersets, f, g)
+    }
+
+    fn apply(&mut self, operation: Operation, f: ZddRef, g: ZddRef) -> ZddRef {
+        if let Some(r) = self.terminal_case(operation, f, g) {
+            return r;
+        }
+        let symmetric = matches!(operation, Union | Intersection | Join);
+        let key = if symmetric && g < f {
+            (operation, g, f)
+        } else {
+            (operation, f, g)
+        };
+        if let Some(&r) = self.computed.get(&key) {
+            return r;
+        }
+        // at least one of them is a node, see `terminal_case`
+        let element = match (self.top(f), self.top(g)) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b).unwrap(),
+        };
+        let (f_low, f_high) = self.cofactors(f, element);
+        let (g_low, g_high) = self.cofactors(g, element);
+        let (low, high) = match operation {
+            Join => {
+                let low = self.join(f_low, g_low);
+                let high_low = self.join(f_high, g_low);
+                let low_high = self.join(f_low, g_high);
+                let high_high = self.join(f_high, g_high);
+                let high = self.union(high_low, low_high);
+                (low, self.union(high, high_high))
+            }
+            Subsets => {
+                // a set without the element fits in sets with or without it
+                let g_any = self.union(g_low, g_high);
+                (self.subsets(f_low, g_any), self.subsets(f_high, g_high))
+            }
+            Supersets => {
+                let high_low = self.supersets(f_high, g_low);
+                let high_high = self.supersets(f_high, g_high);
+                (
+                    self.supersets(f_low, g_low),
+                    self.union(high_low, high_high),
+                )
+            }
+            _ => (
+                self.apply(operation, f_low, g_low),
+                self.apply(operation, f_high, g_high),
+            ),
+        };
+        let r = self.make(element, low, high);
+        self.computed.insert(key, r);
+        r
+    }
+
+    // Result known without looking into the diagrams. Always known when both are constants.
+    fn terminal_case(&self, operation: Operation, f: ZddRef, g: ZddRef) -> Option<ZddRef> {
+        let empty = ZddRef::EMPTY;
+        match operation {
+            Union if f == empty || f == g => Some(g),
+            Union if g == empty => Some(f),
+            Intersection if f == empty || g == empty => Some(empty),
+            Intersection if f == g => Some(f),
+            Intersection if f.is_constant() && g.is_constant() => Some(empty),
+            Difference if f == empty || f == g => Some(empty),
+            Difference if g == empty => Some(f),
+            Difference if f.is_constant() && g.is_constant() => Some(f),
+            Join if f == empty || g == empty => Some(empty),
+            Join if f == ZddRef::BASE => Some(g),
+            Join if g == ZddRef::BASE => Some(f),
+            Subsets if f == empty || g == empty => Some(empty),
+            Subsets if f == g => Some(f),
+            Subsets if f == ZddRef::BASE => Some(f),
+            Subsets if g == ZddRef::BASE => Some(self.empty_set_only(f)),
+            Supersets if f == empty || g == empty => Some(empty),
+            Supersets if f == g || self.has_empty_set(g) => Some(f),
+            Supersets if f == ZddRef::BASE => Some(empty),
+            _ => None,
+        }
+    }
+
+    fn has_empty_set(&self, f: ZddRef) -> bool {
+        let mut f = f;
+        while !f.is_constant() {
+            f = self.nodes[f.0].low;
+        }
+        f == ZddRef::BASE
+    }
+
+    // The family of the empty set if `f` has it, the empty family otherwise.
+    fn empty_set_only(&self, f: ZddRef) -> ZddRef {
+        if self.has_empty_set(f) {
+            ZddRef::BASE
+        } else {
+            ZddRef::EMPTY
+        }
+    }
+
+    pub fn contains(&self, f: ZddRef, set: &[i32]) -> bool {
+        let mut set = set.to_vec();
+        set.sort_unstable();
+        set.dedup();
+        let mut f = f;
+        let mut elements = set.iter().peekable();
+        while let Some(element) = self.top(f) {
+            let node = self.nodes[f.0];
+            f = if elements.next_if_eq(&&element).is_some() {
+                node.high
+            } else {
+                node.low
+            };
+        }
+        elements.peek().is_none() && f == ZddRef::BASE
+    }
+
+    /// Number of sets of `f`.
+    pub fn count(&self, f: ZddRef) -> BigUint {
+        let mut cache = HashMap::new();
+        self.count_cached(f, &mut cache)
+    }
+
+    fn count_cached(&self, f: ZddRef, cache: &mut HashMap<ZddRef, BigUint>) -> BigUint {
+        if f.is_constant() {
+            return BigUint::from((f == ZddRef::BASE) as u64);
+        }
+        if let Some(count) = cache.get(&f) {
+            return count.clone();
+        }
+        let ZddNode { low, high, .. } = self.nodes[f.0];
+        let count = self.count_cached(low, cache) + self.count_cached(high, cache);
+        cache.insert(f, count.clone());
+        count
+    }
+
+    /// Number of nodes of `f`, constants left out.
+    pub fn size(&self, f: ZddRef) -> usize {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![f];
+        let mut size = 0;
+        while let Some(f) = stack.pop() {
+            if f.is_constant() || seen[f.0] {
+                continue;
+            }
+            seen[f.0] = true;
+            size += 1;
+            stack.push(self.nodes[f.0].low);
+            stack.push(self.nodes[f.0].high);
+        }
+        size
+    }
+
+    /// Sets of `f`, each in increasing order, sorted.
+    pub fn to_vec(&self, f: ZddRef) -> Vec<Vec<i32>> {
+        let mut sets = vec![];
+        self.collect_sets(f, &mut vec![], &mut sets);
+        sets.sort_unstable();
+        sets
+    }
+
+    fn collect_sets(&self, f: ZddRef, prefix: &mut Vec<i32>, sets: &mut Vec<Vec<i32>>) {
+        match f {
+            ZddRef::EMPTY => {}
+            ZddRef::BASE => sets.push(prefix.clone()),
+            _ => {
+                let ZddNode { element, low, high } = self.nodes[f.0];
+                self.collect_sets(low, prefix, sets);
+                prefix.push(element);
+                self.collect_sets(high, prefix, sets);
+                prefix.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod zdd_test {
+    use crate::big_uint::BigUint;
+    use crate::zdd::{Zdd, ZddRef};
+
+    // Subsets of {0, 1, 2, 3} picked by a bitfield, sorted as by `Zdd::to_vec`.
+    fn sets_of(bits: u32) -> Vec<Vec<i32>> {
+        sorted(
+            (0..16)
+                .filter(|s| bits & 1 << s != 0)
+                .map(|s: i32| (0..4).filter(|e| s & 1 << e != 0).collect())
+                .collect(),
+        )
+    }
+
+    fn is_subset(a: &[i32], b: &[i32]) -> bool {
+        a.iter().all(|x| b.contains(x))
+    }
+
+    fn sorted(mut sets: Vec<Vec<i32>>) -> Vec<Vec<i32>> {
+        for set in &mut sets {
+            set.sort_unstable();
+            set.dedup();
+        }
+        sets.sort_unstable();
+        sets.dedup();
+        sets
+    }
+
+    #[test]
+    fn test_operations_match_vectors() {
+        let families = [
+            0x0000, 0x0001, 0x8000, 0x1234, 0xf0f0, 0x6996, 0xfffe, 0xffff,
+        ];
+        let mut zdd = Zdd::new();
+        for a in families {
+            for b in families {
+                let (a, b) = (sets_of(a), sets_of(b));
+                let f = zdd.family(&a);
+                let g = zdd.family(&b);
+                assert_eq!(zdd.to_vec(f), a);
+
+                let union = zdd.union(f, g);
+                let expected = sorted([a.clone(), b.clone()].concat());
+                assert_eq!(zdd.to_vec(union), expected);
+
+                let intersection = zdd.intersection(f, g);
+                let expected: Vec<_> = a.iter().filter(|s| b.contains(s)).cloned().collect();
+                assert_eq!(zdd.to_vec(intersection), expected);
+
+                let difference = zdd.difference(f, g);
+                let expected: Vec<_> = a.iter().filter(|s| !b.contains(s)).cloned().collect();
+                assert_eq!(zdd.to_vec(difference), expected);
+
+                let join = zdd.join(f, g);
+                let expected = a
+                    .iter()
+                    .flat_map(|s| b.iter().map(move |t| [s.clone(), t.clone()].concat()))
+                    .collect();
+                assert_eq!(zdd.to_vec(join), sorted(expected));
+
+                let subsets = zdd.subsets(f, g);
+                let expected: Vec<_> = a
+                    .iter()
+                    .filter(|s| b.iter().any(|t| is_subset(s, t)))
+                    .cloned()
+                    .collect();
+                assert_eq!(zdd.to_vec(subsets), expected);
+
+                let supersets = zdd.supersets(f, g);
+                let expected: Vec<_> = a
+                    .iter()
+                    .filter(|s| b.iter().any(|t| is_subset(t, s)))
+                    .cloned()
+                    .collect();
+                assert_eq!(zdd.to_vec(supersets), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_powerset() {
+        let mut zdd = Zdd::new();
+        let f = zdd.powerset(&[3, 1, 2]);
+        assert_eq!(
+            zdd.to_vec(f),
+            vec![
+                vec![],
+                vec![1],
+                vec![1, 2],
+                vec![1, 2, 3],
+                vec![1, 3],
+                vec![2],
+                vec![2, 3],
+                vec![3],
+            ]
+        );
+        assert_eq!(zdd.powerset(&[]), ZddRef::BASE);
+
+        // far too many sets to list
+        let elements: Vec<i32> = (-50..50).collect();
+        let f = zdd.powerset(&elements);
+        assert_eq!(zdd.size(f), 100);
+        assert_eq!(zdd.count(f), BigUint::power_of_two(100));
+        assert!(zdd.contains(f, &[-50, 0, 49]));
+        assert!(!zdd.contains(f, &[50]));
+
+        // the empty set and the singletons of 0 to 49, then those containing an even element
+        let singletons = zdd.family(&(0..100).map(|e| vec![e]).collect::<Vec<_>>());
+        let base = zdd.singleton(&[]);
+        let small = zdd.union(singletons, base);
+        let small = zdd.subsets(f, small);
+        assert_eq!(zdd.count(small), BigUint::from(51));
+        let evens = zdd.family(&(-50..50).step_by(2).map(|e| vec![e]).collect::<Vec<_>>());
+        let even = zdd.supersets(small, evens);
+        assert_eq!(zdd.count(even), BigUint::from(25));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut zdd = Zdd::new();
+        let f = zdd.family(&[vec![1, 2], vec![3], vec![2, 1, 1]]);
+        let g = zdd.family(&[vec![3], vec![2, 1]]);
+        assert_eq!(f, g);
+        assert_eq!(zdd.count(f), BigUint::from(2));
+        assert!(zdd.contains(f, &[2, 1]));
+        assert!(!zdd.contains(f, &[1]));
+        assert!(!zdd.contains(f, &[]));
+        let a = zdd.singleton(&[1]);
+        let b = zdd.singleton(&[2]);
+        let joined = zdd.join(a, b);
+        let c = zdd.singleton(&[3]);
+        assert_eq!(zdd.union(joined, c), f);
+        assert_eq!(zdd.difference(f, g), ZddRef::EMPTY);
+    }
+}
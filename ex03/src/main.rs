@@ -1,90 +1,114 @@
+use boolean_evaluation::assignment::Assignment;
+use boolean_evaluation::error::ParseError;
+use boolean_evaluation::error::ParseError::UnknownToken;
 use boolean_evaluation::tree::Tree;
 use std::str::FromStr;
 
-fn eval_formula(str: &str) -> bool {
-    let node = Tree::from_str(str).unwrap();
-    node.root.eval()
+/// Evaluates a formula of constants. Variables have no value to take here, the first one is
+/// reported as an unknown token.
+fn eval_formula(str: &str) -> Result<bool, ParseError> {
+    let node = Tree::from_str(str)?;
+    if let Some(position) = str.find(|c: char| c.is_ascii_uppercase() || c == '[') {
+        let token = str[position..].chars().next().unwrap();
+        return Err(UnknownToken { position, token });
+    }
+    Ok(node.root.eval_with(&Assignment::default()))
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 && !args[1].is_empty() {
-        eval_formula(&args[1]);
+        let _ = eval_formula(&args[1]);
     } else {
-        eval_formula("10=");
+        let _ = eval_formula("10=");
     }
 }
 
 #[cfg(test)]
 mod test_bool_eval {
     use super::*;
-    use std::panic;
+    use boolean_evaluation::error::ParseError::*;
 
     #[test]
     fn test_inputs() {
-        panic::set_hook(Box::new(|_info| {
-            // do nothing
-        }));
-
-        let invalid_unary = panic::catch_unwind(|| eval_formula("!1"));
-        let not_enough_tokens = panic::catch_unwind(|| eval_formula("!"));
-        let invalid_token = panic::catch_unwind(|| eval_formula("@"));
-        let no_input = panic::catch_unwind(|| eval_formula(""));
-        assert!(invalid_unary.is_err());
-        assert!(not_enough_tokens.is_err());
-        assert!(invalid_token.is_err());
-        assert!(no_input.is_err());
+        assert!(matches!(
+            Tree::from_str("!1"),
+            Err(MissingOperands { position: 0, .. })
+        ));
+        assert!(matches!(
+            Tree::from_str("!"),
+            Err(MissingOperands { position: 0, .. })
+        ));
+        assert!(matches!(
+            Tree::from_str("@"),
+            Err(UnknownToken {
+                position: 0,
+                token: '@'
+            })
+        ));
+        assert_eq!(Tree::from_str("").unwrap_err(), EmptyInput);
+        assert!(matches!(
+            eval_formula("10&&"),
+            Err(MissingOperands { position: 3, .. })
+        ));
+        assert_eq!(
+            eval_formula("1A&"),
+            Err(UnknownToken {
+                position: 1,
+                token: 'A'
+            })
+        );
     }
 
     #[test]
     fn test_ands() {
-        assert!(!eval_formula("10&"));
-        assert!(eval_formula("11&"));
-        assert!(!eval_formula("00&"));
+        assert!(!eval_formula("10&").unwrap());
+        assert!(eval_formula("11&").unwrap());
+        assert!(!eval_formula("00&").unwrap());
     }
 
     #[test]
     fn test_ors() {
-        assert!(eval_formula("10|"));
-        assert!(eval_formula("11|"));
-        assert!(!eval_formula("00|"));
+        assert!(eval_formula("10|").unwrap());
+        assert!(eval_formula("11|").unwrap());
+        assert!(!eval_formula("00|").unwrap());
     }
 
     #[test]
     fn test_xors() {
-        assert!(eval_formula("10^"));
-        assert!(!eval_formula("11^"));
-        assert!(!eval_formula("00^"));
+        assert!(eval_formula("10^").unwrap());
+        assert!(!eval_formula("11^").unwrap());
+        assert!(!eval_formula("00^").unwrap());
     }
 
     #[test]
     fn test_negs() {
-        assert!(eval_formula("0!"));
-        assert!(!eval_formula("1!"));
+        assert!(eval_formula("0!").unwrap());
+        assert!(!eval_formula("1!").unwrap());
     }
 
     #[test]
     fn test_imply() {
-        assert!(eval_formula("11>"));
-        assert!(eval_formula("00>"));
-        assert!(eval_formula("01>"));
-        assert!(!eval_formula("10>"));
+        assert!(eval_formula("11>").unwrap());
+        assert!(eval_formula("00>").unwrap());
+        assert!(eval_formula("01>").unwrap());
+        assert!(!eval_formula("10>").unwrap());
     }
 
     #[test]
     fn test_leqs() {
-        assert!(eval_formula("11="));
-        assert!(!eval_formula("01="));
-        assert!(eval_formula("00="));
-        assert!(!eval_formula("10="));
+        assert!(eval_formula("11=").unwrap());
+        assert!(!eval_formula("01=").unwrap());
+        assert!(eval_formula("00=").unwrap());
+        assert!(!eval_formula("10=").unwrap());
     }
 
     #[test]
     fn test_examples() {
-        assert!(!eval_formula("10&"));
-        assert!(eval_formula("10|"));
-        assert!(eval_formula("11>"));
-        assert!(!eval_formula("10="));
-        assert!(eval_formula("1011||="));
+        assert!(!eval_formula("10&").unwrap());
+        assert!(eval_formula("10|").unwrap());
+        assert!(eval_formula("11>").unwrap());
+        assert!(!eval_formula("10=").unwrap());
+        assert!(eval_formula("1011||=").unwrap());
     }
 }
@@ -1,10 +1,11 @@
-use boolean_evaluation::cnf::node_to_cnf;
+use boolean_evaluation::cnf::Cnf;
 use boolean_evaluation::tree::Tree;
 use std::str::FromStr;
 
 fn conjunctive_normal_form(formula: &str) -> String {
     let node = Tree::from_str(formula).unwrap();
-    node_to_cnf(node.root).to_rpn()
+    let variables = node.variable_list.clone().unwrap_or_default();
+    Cnf::from(&node.root).to_node(&variables).to_rpn()
 }
 
 fn main() {
@@ -28,14 +29,12 @@ mod cnf_42_test {
 
         assert_eq!(conjunctive_normal_form("AB|C&"), "AB|C&");
 
-        // (((A | B) | C) | D) == (((C | D) | B) | A)
-        // this is wrong??
-        // assert_eq!(conjunctive_normal_form("AB|C|D|"), "ABCD|||");
+        assert_eq!(conjunctive_normal_form("AB|C|D|"), "ABCD|||");
 
-        // assert_eq!(conjunctive_normal_form("AB&C&D&"), "ABCD&&&");
+        assert_eq!(conjunctive_normal_form("AB&C&D&"), "ABCD&&&");
 
-        // assert_eq!(conjunctive_normal_form("AB&!C!|"), "A!B!C!||");
+        assert_eq!(conjunctive_normal_form("AB&!C!|"), "A!B!C!||");
 
-        // assert_eq!(conjunctive_normal_form("AB|!C!&"), "A!B!C!&&");
+        assert_eq!(conjunctive_normal_form("AB|!C!&"), "A!B!C!&&");
     }
 }